@@ -45,7 +45,7 @@ fn bench_iterator_next(c: &mut Criterion) {
                 || Cursor::new(black_box(&buf)),
                 |mut cur| {
                     let mut sum = 0u64;
-                    while let Some(b) = cur.next() {
+                    for b in cur.by_ref() {
                         sum = sum.wrapping_add(b as u64);
                     }
                     black_box(sum)
@@ -367,6 +367,82 @@ fn bench_skip_while(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_backtracking(c: &mut Criterion) {
+    let mut group = c.benchmark_group("backtracking");
+    for &size in &[4_096usize, 32_768, 262_144, 1_048_576] {
+        // Alternatives share long prefixes, so most attempts fail late and roll back.
+        let buf = make_repeated(b"key=value; key=42; key:=x; ", size);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_function(format!("attempt_alternatives/{}B", size), |b| {
+            b.iter_batched(
+                || Cursor::new(black_box(&buf)),
+                |mut cur| {
+                    let mut hits = 0usize;
+                    while !cur.eof() {
+                        let m = cur.mark();
+                        // Alternative 1: key=<int>;
+                        if cur.expect_bytes(b"key=").is_some()
+                            && cur.take_int_ascii().is_some()
+                            && cur.expect_byte(b';').is_some()
+                        {
+                            hits += 1;
+                            continue;
+                        }
+                        cur.reset(m);
+                        // Alternative 2: key=<ident>;
+                        if cur.expect_bytes(b"key=").is_some()
+                            && cur.take_ident_ascii().is_some()
+                            && cur.expect_byte(b';').is_some()
+                        {
+                            hits += 1;
+                            continue;
+                        }
+                        cur.reset(m);
+                        let _ = cur.next_byte();
+                    }
+                    black_box(hits)
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_separated_ints(c: &mut Criterion) {
+    let mut group = c.benchmark_group("separated");
+    for &size in &[4_096usize, 32_768, 262_144, 1_048_576] {
+        let mut buf = make_repeated(b"12,345,6789,0,42,", size);
+        // Terminate the list on a digit so it parses as a single run.
+        *buf.last_mut().unwrap() = b'1';
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_function(format!("comma_ints/{}B", size), |b| {
+            b.iter_batched(
+                || Cursor::new(black_box(&buf)),
+                |mut cur| {
+                    let mut items = 0usize;
+                    while cur.take_int_ascii().is_some() {
+                        items += 1;
+                        if cur.expect_byte(b',').is_none() {
+                            break;
+                        }
+                    }
+                    black_box(items)
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(format!("take_dotted_ints/{}B", size), |b| {
+            b.iter_batched(
+                || Cursor::new(black_box(&buf)),
+                |mut cur| black_box(cur.take_dotted_ints(b',', usize::MAX).map(|v| v.len())),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
 pub fn criterion_benchmark(c: &mut Criterion) {
     bench_next_byte(c);
     bench_iterator_next(c);
@@ -380,6 +456,8 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     bench_starts_with(c);
     bench_take_space(c);
     bench_skip_while(c);
+    bench_backtracking(c);
+    bench_separated_ints(c);
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,54 @@
+/// A set of bytes backed by a 256-bit mask, for closure-free class checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ByteSet([u64; 4]);
+
+impl ByteSet {
+    #[inline]
+    pub const fn new() -> Self {
+        Self([0; 4])
+    }
+    #[inline]
+    pub const fn from_bytes(bytes: &[u8]) -> Self {
+        let mut set = Self::new();
+        let mut k = 0;
+        while k < bytes.len() {
+            set = set.with(bytes[k]);
+            k += 1;
+        }
+        set
+    }
+    #[inline]
+    pub const fn from_range(lo: u8, hi: u8) -> Self {
+        let mut set = Self::new();
+        let mut b = lo as usize;
+        while b <= hi as usize {
+            set = set.with(b as u8);
+            b += 1;
+        }
+        set
+    }
+
+    #[inline]
+    pub const fn with(mut self, b: u8) -> Self {
+        self.0[(b >> 6) as usize] |= 1 << (b & 63);
+        self
+    }
+    #[inline]
+    pub const fn union(self, other: Self) -> Self {
+        Self([
+            self.0[0] | other.0[0],
+            self.0[1] | other.0[1],
+            self.0[2] | other.0[2],
+            self.0[3] | other.0[3],
+        ])
+    }
+    #[inline]
+    pub fn insert(&mut self, b: u8) {
+        *self = self.with(b);
+    }
+
+    #[inline]
+    pub const fn contains(&self, b: u8) -> bool {
+        self.0[(b >> 6) as usize] & (1 << (b & 63)) != 0
+    }
+}
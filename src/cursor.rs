@@ -1,15 +1,125 @@
-use core::ops::Range;
+use core::fmt::Write;
+use core::ops::{ControlFlow, Range};
+use std::borrow::Cow;
+
+use crate::byteset::ByteSet;
+#[cfg(feature = "trace")]
+use crate::trace::{TraceEvent, TraceLog};
 
 #[derive(Debug)]
 pub struct Cursor<'a> {
     buf: &'a [u8],
     i: usize,
+    budget: usize,
+    budget_exhausted: bool,
+    #[cfg(feature = "trace")]
+    trace: std::sync::Mutex<TraceLog>,
 }
 
 impl<'a> Cursor<'a> {
     #[inline]
     pub const fn new(buf: &'a [u8]) -> Self {
-        Self { buf, i: 0 }
+        Self {
+            buf,
+            i: 0,
+            budget: usize::MAX,
+            budget_exhausted: false,
+            #[cfg(feature = "trace")]
+            trace: std::sync::Mutex::new(TraceLog::new()),
+        }
+    }
+    #[inline]
+    pub const fn new_at_end(buf: &'a [u8]) -> Self {
+        Self {
+            buf,
+            i: buf.len(),
+            budget: usize::MAX,
+            budget_exhausted: false,
+            #[cfg(feature = "trace")]
+            trace: std::sync::Mutex::new(TraceLog::new()),
+        }
+    }
+    /// Creates a cursor whose open-ended scans may cover at most
+    /// `max_scan_bytes` in total. A scan that would go past the cap stops
+    /// there and sets [`budget_exhausted`](Self::budget_exhausted); a scan
+    /// that ends exactly at the cap does not. Then:
+    ///
+    /// - span- and count-returning scans return what they covered so far:
+    ///   `skip_while`, `take_while`, `skip_until`, `skip_while_result`,
+    ///   `skip_while_pair`, `for_each_while`, `take_while_map`,
+    ///   `take_while_char_counting`, `take_escaped_until`, `take_arg` and the
+    ///   helpers built on them (whitespace skipping, `take_through_any`,
+    ///   `skip_lines`, the `_dyn` and `_slice` variants, ...);
+    /// - token parsers fail and roll back rather than return a partial
+    ///   token: `take_int_ascii`, `take_ident_ascii`,
+    ///   `take_ident_starting_alpha`, `take_ranges`, `take_run`,
+    ///   `expect_run`, `take_set_bounded`, `take_line_info`,
+    ///   `take_crlf_line`, `take_logical_line`, `skip_json_value` and the
+    ///   number parsers.
+    ///
+    /// Not budgeted: fixed-length reads and matches; searches
+    /// (`find_subslice`, `split_once`, `take_cstr`, `skip_to_first_of`,
+    /// `take_between`, `skip_block_comment`); indentation and column helpers
+    /// (`expect_indent`, `consume_dedent`, `skip_to_col`); decoders
+    /// (`decode_with_table`, `copy_normalized_newlines`); and
+    /// `advance_until_rolling_boundary`.
+    ///
+    /// Budget spent by a failed scan is not refunded. [`fork`](Self::fork)
+    /// and [`adopt`](Self::adopt) carry the budget across; other derived
+    /// cursors are unbudgeted.
+    #[inline]
+    pub const fn with_budget(buf: &'a [u8], max_scan_bytes: usize) -> Self {
+        let mut c = Self::new(buf);
+        c.budget = max_scan_bytes;
+        c
+    }
+
+    /// Scan budget left; `usize::MAX` for cursors made without one.
+    #[inline]
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+    /// Whether a scan has been cut short by the budget, as opposed to ending
+    /// on a rejected byte or EOF. Stays set once tripped.
+    #[inline]
+    pub fn budget_exhausted(&self) -> bool {
+        self.budget_exhausted
+    }
+    /// End of the region the next scan may cover.
+    #[inline]
+    fn scan_limit(&self) -> usize {
+        self.i.saturating_add(self.budget).min(self.buf.len())
+    }
+    /// Charges a scan that began at `start`. `cut` means it stopped at the
+    /// limit on a byte it would otherwise have consumed.
+    #[inline]
+    fn charge_scan(&mut self, start: usize, cut: bool) {
+        self.budget -= self.i - start;
+        self.budget_exhausted |= cut;
+    }
+    /// Runs a token parser, rolling it back and failing if the budget runs
+    /// out during its scans.
+    #[inline]
+    fn within_budget<T>(&mut self, f: impl FnOnce(&mut Self) -> Option<T>) -> Option<T> {
+        let start = self.i;
+        let was_exhausted = core::mem::replace(&mut self.budget_exhausted, false);
+        let out = f(self);
+        if self.budget_exhausted {
+            self.i = start;
+            return None;
+        }
+        self.budget_exhausted = was_exhausted;
+        out
+    }
+
+    /// Points the cursor at a new buffer, keeping the position. Returns `true`
+    /// if the position had to be clamped because `buf` is shorter than it.
+    #[inline]
+    pub fn rebind(&mut self, buf: &'a [u8]) -> bool {
+        self.buf = buf;
+        let clamped = self.i > buf.len();
+        self.i = self.i.min(buf.len());
+        clamped
     }
 
     // Basic queries
@@ -22,14 +132,55 @@ impl<'a> Cursor<'a> {
         self.i
     }
     #[inline]
+    pub fn at_start(&self) -> bool {
+        self.i == 0
+    }
+    #[inline]
+    pub fn at_eof(&self) -> bool {
+        self.eof()
+    }
+    #[inline]
+    pub fn past(&self, pos: usize) -> bool {
+        self.i > pos
+    }
+    #[inline]
     pub fn remaining(&self) -> usize {
         self.buf.len().saturating_sub(self.i)
     }
     #[inline]
+    pub fn has_remaining(&self, n: usize) -> bool {
+        self.remaining() >= n
+    }
+    #[inline]
+    pub fn require_remaining(&self, n: usize) -> Option<()> {
+        self.has_remaining(n).then_some(())
+    }
+    /// Fraction of the buffer consumed, in `0.0..=1.0`; `0.0` for an empty buffer.
+    #[inline]
+    pub fn progress(&self) -> f64 {
+        if self.buf.is_empty() {
+            0.0
+        } else {
+            self.i as f64 / self.buf.len() as f64
+        }
+    }
+    #[inline]
     pub fn as_slice(&self) -> &'a [u8] {
         &self.buf[self.i..]
     }
     #[inline]
+    pub fn finish(self) -> &'a [u8] {
+        self.as_slice()
+    }
+    #[inline]
+    pub fn to_owned_remaining(&self) -> Vec<u8> {
+        self.as_slice().to_vec()
+    }
+    #[inline]
+    pub fn buffer(&self) -> &'a [u8] {
+        self.buf
+    }
+    #[inline]
     pub fn len(&self) -> usize {
         self.buf.len()
     }
@@ -45,7 +196,7 @@ impl<'a> Cursor<'a> {
     }
     #[inline]
     pub fn peek_n(&self, n: usize) -> Option<u8> {
-        self.buf.get(self.i + n).copied()
+        self.buf.get(self.i.checked_add(n)?).copied()
     }
     #[inline]
     pub fn next_byte(&mut self) -> Option<u8> {
@@ -53,6 +204,9 @@ impl<'a> Cursor<'a> {
         self.i += 1;
         Some(b)
     }
+    /// Advances exactly `n` bytes, returning the span. All-or-nothing: if fewer
+    /// than `n` bytes remain, returns `None` and leaves the cursor unchanged.
+    /// See [`advance_saturating`](Self::advance_saturating) for a partial advance.
     #[inline]
     pub fn advance(&mut self, n: usize) -> Option<Range<usize>> {
         if n > self.remaining() {
@@ -60,8 +214,45 @@ impl<'a> Cursor<'a> {
         }
         let start = self.i;
         self.i += n;
+        #[cfg(feature = "trace")]
+        if n >= crate::trace::LARGE_ADVANCE {
+            self.trace(TraceEvent::Advance {
+                from: start,
+                to: self.i,
+            });
+        }
         Some(start..self.i)
     }
+    /// Advances up to `n` bytes, stopping at EOF, and returns how many were
+    /// actually advanced.
+    #[inline]
+    pub fn advance_saturating(&mut self, n: usize) -> usize {
+        let n = n.min(self.remaining());
+        self.i += n;
+        n
+    }
+    /// Advances `n` bytes, mirroring `Iterator::advance_by`. If fewer remain,
+    /// moves to EOF and returns `Err(k)` with the `k` bytes left unadvanced.
+    #[inline]
+    pub fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        match n - self.advance_saturating(n) {
+            0 => Ok(()),
+            short => Err(short),
+        }
+    }
+    #[inline]
+    pub fn prev_byte(&mut self) -> Option<u8> {
+        let b = *self.buf.get(self.i.checked_sub(1)?)?;
+        self.i -= 1;
+        Some(b)
+    }
+    #[inline]
+    pub fn rewind(&mut self, n: usize) -> Option<Range<usize>> {
+        let start = self.i.checked_sub(n)?;
+        let end = self.i;
+        self.i = start;
+        Some(start..end)
+    }
     #[inline]
     pub fn skip_byte(&mut self, b: u8) -> Option<Range<usize>> {
         let start = self.i;
@@ -76,16 +267,125 @@ impl<'a> Cursor<'a> {
     // Bookmarking
     #[inline]
     pub fn mark(&self) -> usize {
+        #[cfg(feature = "trace")]
+        self.trace(TraceEvent::Mark { pos: self.i });
         self.i
     }
+    /// Like [`mark`](Self::mark), but records `label` with the position when
+    /// the `trace` feature is on. Without it the label is simply dropped.
+    #[inline]
+    pub fn mark_named(&self, label: &'static str) -> usize {
+        #[cfg(feature = "trace")]
+        {
+            self.trace(TraceEvent::NamedMark { pos: self.i, label });
+            self.i
+        }
+        #[cfg(not(feature = "trace"))]
+        {
+            let _ = label;
+            self.mark()
+        }
+    }
+    /// Moves the cursor back to mark `m`. Out-of-range marks are clamped to the
+    /// buffer end in release builds but trip a `debug_assert!` in debug builds,
+    /// since they usually come from a stale mark or a different buffer.
     #[inline]
     pub fn reset(&mut self, m: usize) {
-        self.i = m.min(self.buf.len());
+        debug_assert!(
+            m <= self.buf.len(),
+            "mark {m} out of range for buffer of length {}",
+            self.buf.len()
+        );
+        let to = m.min(self.buf.len());
+        #[cfg(feature = "trace")]
+        self.trace(TraceEvent::Reset { from: self.i, to });
+        self.i = to;
+    }
+    /// Recent mark/reset/large-advance events, oldest first.
+    #[cfg(feature = "trace")]
+    pub fn trace_events(&self) -> Vec<TraceEvent> {
+        self.trace_log().events()
+    }
+    /// Locks the trace log. A log left behind by a panicking thread is still
+    /// usable, so poisoning is ignored.
+    #[cfg(feature = "trace")]
+    fn trace_log(&self) -> std::sync::MutexGuard<'_, TraceLog> {
+        self.trace
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+    #[cfg(feature = "trace")]
+    #[inline]
+    fn trace(&self, event: TraceEvent) {
+        self.trace_log().record(event);
     }
     #[inline]
     pub fn slice_from(&self, m: usize) -> &'a [u8] {
         &self.buf[m.min(self.buf.len())..self.i.min(self.buf.len())]
     }
+    #[inline]
+    pub fn scanner_at(&self, pos: usize) -> Cursor<'a> {
+        let mut c = Cursor::new(self.buf);
+        c.i = pos.min(self.buf.len());
+        c
+    }
+    /// Splits into a cursor over the consumed prefix and one over the remaining
+    /// suffix, both positioned at their own offset 0.
+    #[inline]
+    pub fn split_cursor(&self) -> (Cursor<'a>, Cursor<'a>) {
+        let (head, tail) = self.buf.split_at(self.i);
+        (Cursor::new(head), Cursor::new(tail))
+    }
+    /// Returns an independent cursor at the same position, sharing the
+    /// remaining scan budget.
+    #[inline]
+    pub fn fork(&self) -> Cursor<'a> {
+        let mut c = self.scanner_at(self.i);
+        c.budget = self.budget;
+        c
+    }
+    /// Moves to the position of `other`, typically a successful [`fork`](Self::fork),
+    /// taking over its remaining scan budget. Debug builds assert that `other`
+    /// scans the same buffer.
+    #[inline]
+    pub fn adopt(&mut self, other: &Cursor<'a>) {
+        debug_assert!(
+            core::ptr::eq(self.buf.as_ptr(), other.buf.as_ptr()),
+            "adopted cursor scans a different buffer"
+        );
+        self.reset(other.i);
+        self.budget = other.budget;
+        self.budget_exhausted |= other.budget_exhausted;
+    }
+    /// Runs `f` with the buffer end capped at `pos() + max_len`, then restores
+    /// the real end. Progress made by `f` is kept.
+    pub fn with_limit<T>(&mut self, max_len: usize, f: impl FnOnce(&mut Cursor<'a>) -> T) -> T {
+        let full = self.buf;
+        let end = self.i.saturating_add(max_len).min(full.len());
+        self.buf = &full[..end];
+        let out = f(self);
+        self.buf = full;
+        out
+    }
+    /// Shrinks `span` (clamped to the buffer) past leading and trailing bytes
+    /// contained in `set`.
+    #[inline]
+    pub fn trim_span(&self, span: Range<usize>, set: &ByteSet) -> Range<usize> {
+        let end = span.end.min(self.buf.len());
+        let mut span = span.start.min(end)..end;
+        while span.start < span.end && set.contains(self.buf[span.start]) {
+            span.start += 1;
+        }
+        while span.start < span.end && set.contains(self.buf[span.end - 1]) {
+            span.end -= 1;
+        }
+        span
+    }
+    #[inline]
+    pub fn region(&self, from: usize, to: usize) -> Cursor<'a> {
+        let to = to.min(self.buf.len());
+        Cursor::new(&self.buf[from.min(to)..to])
+    }
 
     // ASCII whitespace
     #[inline]
@@ -96,18 +396,239 @@ impl<'a> Cursor<'a> {
     pub fn take_space(&mut self) -> Option<Range<usize>> {
         self.take_while(Self::is_space_ascii)
     }
+    #[inline]
+    pub fn skip_space_report(&mut self) -> bool {
+        self.take_space().is_some()
+    }
+    /// Consumes a single `' '`. With `strict`, also fails if a second space
+    /// follows. Nothing is consumed on failure.
+    #[inline]
+    pub fn expect_single_space(&mut self, strict: bool) -> bool {
+        if self.peek() != Some(b' ') || (strict && self.peek_n(1) == Some(b' ')) {
+            return false;
+        }
+        self.i += 1;
+        true
+    }
+    /// Skips chars for which `char::is_whitespace` holds, including non-ASCII
+    /// spaces such as U+00A0. Stops at the first other char or invalid UTF-8
+    /// and returns the number of bytes consumed.
+    pub fn skip_unicode_space(&mut self) -> usize {
+        self.take_while_char_counting(char::is_whitespace).0.len()
+    }
+    /// Consumes chars while `pred` holds, returning the byte span and the number
+    /// of chars consumed. Stops at invalid UTF-8.
+    pub fn take_while_char_counting(
+        &mut self,
+        mut pred: impl FnMut(char) -> bool,
+    ) -> (Range<usize>, usize) {
+        let start = self.i;
+        let limit = self.scan_limit();
+        let mut chars = 0;
+        let mut cut = false;
+        while let Some(ch) = self.peek_char().filter(|&ch| pred(ch)) {
+            if self.i + ch.len_utf8() > limit {
+                cut = true;
+                break;
+            }
+            self.i += ch.len_utf8();
+            chars += 1;
+        }
+        self.charge_scan(start, cut);
+        (start..self.i, chars)
+    }
+    /// Decodes the char at the cursor, `None` at EOF or on invalid UTF-8.
+    fn peek_char(&self) -> Option<char> {
+        let head = &self.as_slice()[..self.remaining().min(4)];
+        head.utf8_chunks().next()?.valid().chars().next()
+    }
+    /// Consumes exactly `spaces` space bytes. Fails without consuming if fewer
+    /// spaces are present or the indentation continues with a space or tab.
+    #[inline]
+    pub fn expect_indent(&mut self, spaces: usize) -> Option<Range<usize>> {
+        let run = self.buf[self.i..]
+            .iter()
+            .take_while(|&&b| b == b' ')
+            .count();
+        if run != spaces || self.peek_n(run) == Some(b'\t') {
+            return None;
+        }
+        self.advance(spaces)
+    }
+    /// At a line start, measures the indentation (tabs expanded to the next
+    /// multiple of `tab_width`). If it is less than `current_indent`, consumes
+    /// it and returns the new level; otherwise consumes nothing. Blank lines
+    /// and positions not at a line start are never dedents.
+    pub fn consume_dedent(&mut self, current_indent: usize, tab_width: usize) -> Option<usize> {
+        if self.i > 0 && self.buf.get(self.i - 1) != Some(&b'\n') {
+            return None;
+        }
+        let tab_width = tab_width.max(1);
+        let mut level = 0;
+        let mut len = 0;
+        for &b in self.as_slice() {
+            match b {
+                b' ' => level += 1,
+                b'\t' => level = (level / tab_width + 1) * tab_width,
+                _ => break,
+            }
+            len += 1;
+        }
+        let blank = matches!(self.peek_n(len), None | Some(b'\n' | b'\r'));
+        if blank || level >= current_indent {
+            return None;
+        }
+        self.i += len;
+        Some(level)
+    }
 
     // Scanning and matching
     #[inline]
     pub fn skip_until(&mut self, b: u8) -> Range<usize> {
         let start = self.i;
-        if let Some(off) = self.buf[self.i..].iter().position(|&x| x == b) {
+        let limit = self.scan_limit();
+        if let Some(off) = self.buf[self.i..limit].iter().position(|&x| x == b) {
             self.i += off;
         } else {
-            self.i = self.buf.len();
+            self.i = limit;
         }
+        let cut = self.i == limit && self.buf.get(limit).is_some_and(|&x| x != b);
+        self.charge_scan(start, cut);
         start..self.i
     }
+    /// Returns the absolute position of the first occurrence of `needle` in the
+    /// remaining buffer, without advancing. An empty needle matches at `pos()`.
+    #[inline]
+    pub fn find_subslice(&self, needle: &[u8]) -> Option<usize> {
+        let hay = self.as_slice();
+        #[cfg(feature = "memchr")]
+        let off = memchr::memmem::find(hay, needle);
+        #[cfg(not(feature = "memchr"))]
+        let off = if needle.is_empty() {
+            Some(0)
+        } else {
+            hay.windows(needle.len()).position(|w| w == needle)
+        };
+        off.map(|off| self.i + off)
+    }
+    /// Moves to the earliest occurrence of any of `needles` without consuming
+    /// it, returning the index of the needle found (the lowest index wins a
+    /// tie). Without a match the cursor ends at EOF and `None` is returned.
+    pub fn skip_to_first_of(&mut self, needles: &[&[u8]]) -> Option<usize> {
+        if needles.iter().any(|n| n.is_empty()) {
+            // An empty needle matches here, as may earlier needles.
+            return needles.iter().position(|n| self.starts_with(n));
+        }
+        let mut heads: Vec<u8> = needles.iter().map(|n| n[0]).collect();
+        heads.sort_unstable();
+        heads.dedup();
+        let set = ByteSet::from_bytes(&heads);
+        let mut from = self.i;
+        loop {
+            let hay = &self.buf[from..];
+            #[cfg(feature = "memchr")]
+            let off = match *heads.as_slice() {
+                [a] => memchr::memchr(a, hay),
+                [a, b] => memchr::memchr2(a, b, hay),
+                [a, b, c] => memchr::memchr3(a, b, c, hay),
+                _ => hay.iter().position(|&b| set.contains(b)),
+            };
+            #[cfg(not(feature = "memchr"))]
+            let off = hay.iter().position(|&b| set.contains(b));
+            let Some(off) = off else { break };
+            let at = from + off;
+            if let Some(idx) = needles.iter().position(|n| self.buf[at..].starts_with(n)) {
+                self.i = at;
+                return Some(idx);
+            }
+            from = at + 1;
+        }
+        self.i = self.buf.len();
+        None
+    }
+    /// Advances until a byte satisfying `stop` (not consumed) or EOF, stepping
+    /// over `esc` together with the byte after it. Returns the span and
+    /// whether a stop byte was found.
+    pub fn take_escaped_until(
+        &mut self,
+        esc: u8,
+        mut stop: impl FnMut(u8) -> bool,
+    ) -> (Range<usize>, bool) {
+        let start = self.i;
+        let limit = self.scan_limit();
+        let mut found = false;
+        let mut cut = false;
+        while let Some(b) = self.peek() {
+            let step = if b == esc {
+                2
+            } else if stop(b) {
+                found = true;
+                break;
+            } else {
+                1
+            };
+            let next = (self.i + step).min(self.buf.len());
+            if next > limit {
+                cut = true;
+                break;
+            }
+            self.i = next;
+        }
+        self.charge_scan(start, cut);
+        (start..self.i, found)
+    }
+    /// Splits the remaining buffer around the first `delim`, returning the
+    /// spans before and after it and consuming to EOF. Leaves the cursor
+    /// unchanged if `delim` is absent.
+    #[inline]
+    pub fn split_once(&mut self, delim: u8) -> Option<(Range<usize>, Range<usize>)> {
+        let off = self.as_slice().iter().position(|&b| b == delim)?;
+        let at = self.i + off;
+        let split = (self.i..at, at + 1..self.buf.len());
+        self.i = self.buf.len();
+        Some(split)
+    }
+    /// Consumes through the first byte in `set`, returning the span before it
+    /// and the delimiter found. At EOF, or when the scan budget runs out, the
+    /// span scanned so far is returned with `None`.
+    #[inline]
+    pub fn take_through_any(&mut self, set: &[u8]) -> (Range<usize>, Option<u8>) {
+        let span = self.skip_while(|b| !set.contains(&b));
+        let delim = self.peek().filter(|b| set.contains(b));
+        if delim.is_some() {
+            self.i += 1;
+        }
+        (span, delim)
+    }
+    #[inline]
+    pub fn count_byte(&self, b: u8) -> usize {
+        self.as_slice().iter().filter(|&&x| x == b).count()
+    }
+    /// Counts non-overlapping occurrences of `needle` in the remaining buffer.
+    /// An empty needle counts as zero occurrences.
+    pub fn count_occurrences(&self, needle: &[u8]) -> usize {
+        if needle.is_empty() {
+            return 0;
+        }
+        let mut scan = self.fork();
+        let mut count = 0;
+        while let Some(at) = scan.find_subslice(needle) {
+            count += 1;
+            scan.i = at + needle.len();
+        }
+        count
+    }
+    /// Capacity hint for splitting the remaining buffer on `delim`. This is an
+    /// upper bound on the number of pieces, not an exact count.
+    #[inline]
+    pub fn estimate_splits(&self, delim: u8) -> usize {
+        self.count_byte(delim) + 1
+    }
+    #[inline]
+    pub fn skip_until_found(&mut self, b: u8) -> (usize, bool) {
+        let span = self.skip_until(b);
+        (span.len(), self.peek() == Some(b))
+    }
 
     #[inline]
     pub fn match_bytes(&mut self, pat: &[u8]) -> Option<Range<usize>> {
@@ -120,6 +641,105 @@ impl<'a> Cursor<'a> {
         }
     }
 
+    /// Succeeds only if the entire remaining input equals `pat`, consuming it.
+    /// The cursor is left unchanged on failure.
+    #[inline]
+    pub fn expect_exact(&mut self, pat: &[u8]) -> bool {
+        if self.as_slice() != pat {
+            return false;
+        }
+        self.i = self.buf.len();
+        true
+    }
+    /// Matches `pat` only if the following byte (if any) is not in `forbidden`.
+    #[inline]
+    pub fn expect_bytes_not_followed_by(
+        &mut self,
+        pat: &[u8],
+        forbidden: &ByteSet,
+    ) -> Option<Range<usize>> {
+        if !self.starts_with(pat)
+            || self
+                .peek_n(pat.len())
+                .is_some_and(|b| forbidden.contains(b))
+        {
+            return None;
+        }
+        self.advance(pat.len())
+    }
+    /// Consumes `pat` repeatedly while the input starts with it, returning the
+    /// number of copies consumed. An empty `pat` consumes nothing.
+    #[inline]
+    pub fn consume_while_prefix(&mut self, pat: &[u8]) -> usize {
+        if pat.is_empty() {
+            return 0;
+        }
+        let mut count = 0;
+        while self.match_bytes(pat).is_some() {
+            count += 1;
+        }
+        count
+    }
+    #[inline]
+    pub fn expect_alt(&mut self, alts: &[&[u8]]) -> Option<usize> {
+        alts.iter().position(|alt| self.expect_bytes(alt).is_some())
+    }
+    /// Like [`match_bytes`](Self::match_bytes) but on failure reports how many
+    /// leading bytes of `pat` matched before the input diverged or ended.
+    #[inline]
+    pub fn match_bytes_prefix(&mut self, pat: &[u8]) -> Result<Range<usize>, usize> {
+        let matched = self
+            .as_slice()
+            .iter()
+            .zip(pat)
+            .take_while(|(a, b)| a == b)
+            .count();
+        if matched < pat.len() {
+            return Err(matched);
+        }
+        let start = self.i;
+        self.i += matched;
+        Ok(start..self.i)
+    }
+    #[inline]
+    pub fn match_bytes_wild(&mut self, pat: &[u8], wildcard: u8) -> Option<Range<usize>> {
+        let window = self.peek_slice(pat.len())?;
+        if window
+            .iter()
+            .zip(pat)
+            .all(|(&b, &p)| p == wildcard || b == p)
+        {
+            self.advance(pat.len())
+        } else {
+            None
+        }
+    }
+    /// Matches `pat`, comparing position `i` ASCII case-insensitively when
+    /// `ci_mask[i]` is set and exactly otherwise. Consumes nothing on mismatch.
+    /// The lengths must agree (checked in debug builds); positions past the
+    /// end of a short mask match exactly.
+    #[inline]
+    pub fn expect_bytes_masked(&mut self, pat: &[u8], ci_mask: &[bool]) -> Option<Range<usize>> {
+        debug_assert_eq!(
+            pat.len(),
+            ci_mask.len(),
+            "ci_mask length must match pattern"
+        );
+        let window = self.peek_slice(pat.len())?;
+        let matched = window.iter().zip(pat).enumerate().all(|(k, (b, p))| {
+            if ci_mask.get(k).copied().unwrap_or(false) {
+                b.eq_ignore_ascii_case(p)
+            } else {
+                b == p
+            }
+        });
+        if matched {
+            self.advance(pat.len())
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn expect_bytes(&mut self, pat: &[u8]) -> Option<Range<usize>> {
         let m = self.mark();
@@ -132,6 +752,330 @@ impl<'a> Cursor<'a> {
         }
     }
 
+    // Lines
+    /// Consumes one line, returning its content span (without the `\n` or
+    /// `\r\n` terminator) and whether a terminator was consumed. `None` at EOF.
+    pub fn take_line_info(&mut self) -> Option<(Range<usize>, bool)> {
+        if self.eof() {
+            return None;
+        }
+        self.within_budget(|c| {
+            let mut span = c.skip_until(b'\n');
+            let terminated = c.skip_byte(b'\n').is_some();
+            if terminated && span.end > span.start && c.buf[span.end - 1] == b'\r' {
+                span.end -= 1;
+            }
+            Some((span, terminated))
+        })
+    }
+
+    /// Consumes one logical line, where a `\\` immediately before `\n`
+    /// continues onto the next physical line (as in Makefiles and shell).
+    /// The span runs up to the last physical line's terminator, excluding it.
+    pub fn take_logical_line(&mut self) -> Option<Range<usize>> {
+        if self.eof() {
+            return None;
+        }
+        let start = self.i;
+        self.within_budget(|c| {
+            loop {
+                let end = c.skip_until(b'\n').end;
+                let continued = end > start && c.buf[end - 1] == b'\\';
+                if c.skip_byte(b'\n').is_none() || !continued {
+                    return Some(start..end);
+                }
+            }
+        })
+    }
+
+    #[inline]
+    pub fn line_start(&self, pos: usize) -> usize {
+        let pos = pos.min(self.buf.len());
+        self.buf[..pos]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |p| p + 1)
+    }
+    #[inline]
+    pub fn line_end(&self, pos: usize) -> usize {
+        let pos = pos.min(self.buf.len());
+        self.buf[pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(self.buf.len(), |p| pos + p)
+    }
+
+    /// Moves past up to `n` `\n` terminators, returning how many were passed.
+    /// If fewer remain, the cursor ends at EOF, or where the scan budget ran
+    /// out.
+    pub fn skip_lines(&mut self, n: usize) -> usize {
+        let mut skipped = 0;
+        while skipped < n {
+            self.skip_until(b'\n');
+            if self.skip_byte(b'\n').is_none() {
+                break;
+            }
+            skipped += 1;
+        }
+        skipped
+    }
+    /// Moves to the start of the next line (after the current one) that begins
+    /// with `prefix`. Returns `false` with the cursor at EOF if none exists,
+    /// or where the scan budget ran out.
+    pub fn skip_to_next_line_starting_with(&mut self, prefix: &[u8]) -> bool {
+        while self.skip_lines(1) == 1 {
+            if self.starts_with(prefix) {
+                return true;
+            }
+        }
+        false
+    }
+    /// Advances up to `n` bytes and returns how many `\n` bytes were passed.
+    #[inline]
+    pub fn advance_counting_lines(&mut self, n: usize) -> usize {
+        let start = self.i;
+        self.i += n.min(self.remaining());
+        self.buf[start..self.i]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count()
+    }
+
+    /// Consumes a line that must end in `\r\n`, returning the content span.
+    /// A bare `\n` terminator or EOF fails without consuming.
+    pub fn take_crlf_line(&mut self) -> Option<Range<usize>> {
+        self.within_budget(|c| {
+            let start = c.i;
+            let nl = c.skip_until(b'\n').end;
+            if c.peek() != Some(b'\n') || nl == start || c.buf[nl - 1] != b'\r' {
+                c.i = start;
+                return None;
+            }
+            c.i = nl + 1;
+            Some(start..nl - 1)
+        })
+    }
+
+    // Columns
+    /// Advances within the current line until the visual column (0-based,
+    /// tabs expanded to the next multiple of `tab_width`) reaches at least
+    /// `target_col`. Returns `false` if the line ends first, leaving the cursor
+    /// at the `\n` or EOF.
+    pub fn skip_to_col(&mut self, target_col: usize, tab_width: usize) -> bool {
+        let tab_width = tab_width.max(1);
+        let line_start = self.line_start(self.i);
+        let mut col = self.buf[line_start..self.i].iter().fold(0, |col, &b| {
+            if b == b'\t' {
+                (col / tab_width + 1) * tab_width
+            } else {
+                col + 1
+            }
+        });
+        while col < target_col {
+            match self.peek() {
+                None | Some(b'\n') => return false,
+                Some(b'\t') => col = (col / tab_width + 1) * tab_width,
+                Some(_) => col += 1,
+            }
+            self.i += 1;
+        }
+        true
+    }
+
+    // Bracketed content
+    /// Consumes one argument of a delimited list, stopping before a top-level
+    /// `delim` or an unmatched `close`. Bytes inside nested `open`/`close`
+    /// pairs are skipped over. The returned span is trimmed of ASCII
+    /// whitespace; the cursor is left at the stopping byte (or EOF).
+    pub fn take_arg(&mut self, delim: u8, open: u8, close: u8) -> Range<usize> {
+        let mut depth = 0usize;
+        let start = self.i;
+        let limit = self.scan_limit();
+        let mut cut = false;
+        while let Some(&b) = self.buf.get(self.i) {
+            if b == open {
+                depth += 1;
+            } else if b == close {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            } else if b == delim && depth == 0 {
+                break;
+            }
+            if self.i == limit {
+                cut = true;
+                break;
+            }
+            self.i += 1;
+        }
+        self.charge_scan(start, cut);
+        let mut span = start..self.i;
+        while span.start < span.end && Self::is_space_ascii(self.buf[span.start]) {
+            span.start += 1;
+        }
+        while span.start < span.end && Self::is_space_ascii(self.buf[span.end - 1]) {
+            span.end -= 1;
+        }
+        span
+    }
+
+    /// Requires `open`, then consumes through the first `close` (no nesting),
+    /// returning the span between them. Rolls back if either is missing.
+    pub fn take_between(&mut self, open: &[u8], close: &[u8]) -> Option<Range<usize>> {
+        let m = self.mark();
+        self.match_bytes(open)?;
+        let Some(end) = self.find_subslice(close) else {
+            self.reset(m);
+            return None;
+        };
+        let content = self.i..end;
+        self.i = end + close.len();
+        Some(content)
+    }
+
+    /// Skips one JSON value (object, array, string, number, `true`, `false` or
+    /// `null`) starting at the cursor. Inside containers only brackets and
+    /// string escapes are tracked, so the contents aren't fully validated.
+    /// Rolls back if the value is malformed or truncated.
+    pub fn skip_json_value(&mut self) -> Option<Range<usize>> {
+        self.within_budget(Self::skip_json_value_inner)
+    }
+    fn skip_json_value_inner(&mut self) -> Option<Range<usize>> {
+        // Identifier-continue bytes, which must not directly follow a literal.
+        const IDENT: ByteSet = ByteSet::from_range(b'a', b'z')
+            .union(ByteSet::from_range(b'A', b'Z'))
+            .union(ByteSet::from_range(b'0', b'9'))
+            .with(b'_');
+        let start = self.i;
+        let first = self.peek()?;
+        let ok = match first {
+            b'{' | b'[' | b'"' => {
+                let limit = self.scan_limit();
+                let ok = if first == b'"' {
+                    self.skip_json_string(limit)
+                } else {
+                    self.skip_json_container(limit)
+                };
+                self.charge_scan(start, false);
+                ok
+            }
+            b't' => self.expect_bytes_not_followed_by(b"true", &IDENT).is_some(),
+            b'f' => self
+                .expect_bytes_not_followed_by(b"false", &IDENT)
+                .is_some(),
+            b'n' => self.expect_bytes_not_followed_by(b"null", &IDENT).is_some(),
+            b'-' | b'0'..=b'9' => self.skip_json_number(),
+            _ => false,
+        };
+        if !ok {
+            self.reset(start);
+            return None;
+        }
+        Some(start..self.i)
+    }
+    /// `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`, which must not
+    /// run straight into another number byte.
+    fn skip_json_number(&mut self) -> bool {
+        let digits = |c: &mut Self| !c.skip_while(|b| b.is_ascii_digit()).is_empty();
+        self.skip_byte(b'-');
+        if self.skip_byte(b'0').is_none() && !digits(self) {
+            return false;
+        }
+        if self.skip_byte(b'.').is_some() && !digits(self) {
+            return false;
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.i += 1;
+            let _ = self.skip_byte(b'+').or_else(|| self.skip_byte(b'-'));
+            if !digits(self) {
+                return false;
+            }
+        }
+        !matches!(
+            self.peek(),
+            Some(b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+        )
+    }
+    /// Next byte of a JSON container or string, or `None` at EOF or `limit`.
+    fn next_json_byte(&mut self, limit: usize) -> Option<u8> {
+        if self.i == limit {
+            self.budget_exhausted |= limit < self.buf.len();
+            return None;
+        }
+        self.next_byte()
+    }
+    fn skip_json_container(&mut self, limit: usize) -> bool {
+        let mut closers = Vec::new();
+        while let Some(b) = self.next_json_byte(limit) {
+            match b {
+                b'{' => closers.push(b'}'),
+                b'[' => closers.push(b']'),
+                b'}' | b']' => {
+                    if closers.pop() != Some(b) {
+                        return false;
+                    }
+                    if closers.is_empty() {
+                        return true;
+                    }
+                }
+                b'"' => {
+                    self.i -= 1;
+                    if !self.skip_json_string(limit) {
+                        return false;
+                    }
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+    fn skip_json_string(&mut self, limit: usize) -> bool {
+        if self.next_json_byte(limit) != Some(b'"') {
+            return false;
+        }
+        while let Some(b) = self.next_json_byte(limit) {
+            match b {
+                b'"' => return true,
+                b'\\' if self.next_json_byte(limit).is_none() => return false,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    // Comments
+    /// Skips a non-nesting block comment delimited by `open` and `close`.
+    /// Returns `false` without consuming if the cursor isn't at `open`. When
+    /// `close` is missing, the comment runs to EOF if `allow_unterminated` is
+    /// set, otherwise the cursor rolls back to the opener and `false` is
+    /// returned.
+    pub fn skip_block_comment(
+        &mut self,
+        open: &[u8],
+        close: &[u8],
+        allow_unterminated: bool,
+    ) -> bool {
+        let m = self.mark();
+        if self.match_bytes(open).is_none() {
+            return false;
+        }
+        match self.find_subslice(close) {
+            Some(at) => {
+                self.i = at + close.len();
+                true
+            }
+            None if allow_unterminated => {
+                self.i = self.buf.len();
+                true
+            }
+            None => {
+                self.reset(m);
+                false
+            }
+        }
+    }
+
     // Identifiers and numbers
     #[inline]
     pub const fn is_ident_start_ascii(b: u8) -> bool {
@@ -144,56 +1088,366 @@ impl<'a> Cursor<'a> {
 
     #[inline]
     pub fn take_ident_ascii(&mut self) -> Option<Range<usize>> {
-        self.take_while(Self::is_ident_continue_ascii)
+        self.within_budget(|c| c.take_while(Self::is_ident_continue_ascii))
+    }
+
+    #[inline]
+    pub fn take_ident_str(&mut self) -> Option<&'a str> {
+        let span = self.take_ident_ascii()?;
+        // Identifier bytes are ASCII, so this never fails.
+        core::str::from_utf8(&self.buf[span]).ok()
+    }
+    /// Returns the leading run of bytes in `set` as text without advancing.
+    /// `None` if the run is empty or not valid UTF-8.
+    #[inline]
+    pub fn peek_token_str(&self, set: &ByteSet) -> Option<&'a str> {
+        let rest = self.as_slice();
+        let len = rest.iter().take_while(|&&b| set.contains(b)).count();
+        if len == 0 {
+            return None;
+        }
+        core::str::from_utf8(&rest[..len]).ok()
     }
 
     #[inline]
     pub fn take_ident_starting_alpha(&mut self) -> Option<Range<usize>> {
-        let start = self.i;
-        match self.peek() {
-            Some(b) if Self::is_ident_start_ascii(b) => self.i += 1,
-            _ => return None,
+        if !self.peek().is_some_and(Self::is_ident_start_ascii) {
+            return None;
         }
-        while let Some(&b) = self.buf.get(self.i) {
-            if !Self::is_ident_continue_ascii(b) {
-                break;
-            }
-            self.i += 1;
+        // Start bytes are a subset of continue bytes.
+        self.take_ident_ascii()
+    }
+
+    #[inline]
+    pub fn at_word_start(&self) -> bool {
+        self.i
+            .checked_sub(1)
+            .and_then(|p| self.buf.get(p))
+            .is_none_or(|&b| !Self::is_ident_continue_ascii(b))
+    }
+    /// Whether `pos` separates an identifier-continue byte from a byte that
+    /// is not one (in either order), or lies at a buffer edge. Positions past
+    /// the end are never boundaries.
+    #[inline]
+    pub fn is_token_boundary(&self, pos: usize) -> bool {
+        if pos > self.buf.len() {
+            return false;
         }
-        Some(start..self.i)
+        if pos == 0 || pos == self.buf.len() {
+            return true;
+        }
+        Self::is_ident_continue_ascii(self.buf[pos - 1])
+            != Self::is_ident_continue_ascii(self.buf[pos])
+    }
+
+    /// Matches the first keyword in `kws` that is not immediately followed by
+    /// an identifier-continue byte, returning its index and span.
+    pub fn match_keyword_any(&mut self, kws: &[&[u8]]) -> Option<(usize, Range<usize>)> {
+        let (idx, kw) = kws.iter().enumerate().find(|(_, kw)| {
+            self.starts_with(kw)
+                && !self
+                    .peek_n(kw.len())
+                    .is_some_and(Self::is_ident_continue_ascii)
+        })?;
+        let span = self.advance(kw.len())?;
+        Some((idx, span))
     }
 
     #[inline]
     pub fn take_int_ascii(&mut self) -> Option<Range<usize>> {
-        self.take_while(|b| b.is_ascii_digit())
+        self.within_budget(|c| c.take_while(|b| b.is_ascii_digit()))
+    }
+
+    #[inline]
+    pub fn take_digits_exact(&mut self, count: usize) -> Option<Range<usize>> {
+        if !self.peek_slice(count)?.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        self.advance(count)
+    }
+
+    /// Parses `[0-9]+` as a `u64`, scanning with
+    /// [`take_int_ascii`](Self::take_int_ascii). Consumes nothing if there are
+    /// no digits or the value overflows.
+    #[inline]
+    pub fn parse_u64_ascii(&mut self) -> Option<u64> {
+        let m = self.mark();
+        let span = self.take_int_ascii()?;
+        let value = self.buf[span].iter().try_fold(0u64, |acc, &d| {
+            acc.checked_mul(10)?.checked_add((d - b'0') as u64)
+        });
+        if value.is_none() {
+            self.reset(m);
+        }
+        value
+    }
+
+    /// Parses an optionally signed decimal integer. Rolls back on overflow
+    /// (`i64::MIN` itself is accepted) or when no digits follow the sign.
+    pub fn parse_i64(&mut self) -> Option<i64> {
+        let m = self.mark();
+        let negative = self.skip_byte(b'-').is_some();
+        if !negative {
+            self.skip_byte(b'+');
+        }
+        let value = self.parse_u64_ascii().and_then(|mag| {
+            if negative {
+                0i64.checked_sub_unsigned(mag)
+            } else {
+                i64::try_from(mag).ok()
+            }
+        });
+        if value.is_none() {
+            self.reset(m);
+        }
+        value
+    }
+    /// Same as [`parse_i64`](Self::parse_i64), named to pair with
+    /// [`parse_u64_ascii`](Self::parse_u64_ascii).
+    #[inline]
+    pub fn parse_i64_ascii(&mut self) -> Option<i64> {
+        self.parse_i64()
+    }
+
+    /// Parses a decimal float (`[+-]digits[.digits][e[+-]digits]`) and accepts
+    /// it only if it lies within `lo..=hi`. NaN is never accepted. Rolls back
+    /// on malformed input or an out-of-range value.
+    pub fn parse_f64_in_range(&mut self, lo: f64, hi: f64) -> Option<f64> {
+        let m = self.mark();
+        let value = self
+            .take_float_ascii()
+            .and_then(|span| {
+                core::str::from_utf8(&self.buf[span])
+                    .ok()?
+                    .parse::<f64>()
+                    .ok()
+            })
+            .filter(|v| (lo..=hi).contains(v));
+        if value.is_none() {
+            self.reset(m);
+        }
+        value
+    }
+    fn take_float_ascii(&mut self) -> Option<Range<usize>> {
+        self.within_budget(Self::take_float_ascii_inner)
+    }
+    fn take_float_ascii_inner(&mut self) -> Option<Range<usize>> {
+        let m = self.mark();
+        let _ = self.skip_byte(b'+').or_else(|| self.skip_byte(b'-'));
+        let int = self.skip_while(|b| b.is_ascii_digit()).len();
+        let frac = match self.peek_n(1) {
+            Some(b) if self.peek() == Some(b'.') && b.is_ascii_digit() => {
+                self.i += 1;
+                self.skip_while(|b| b.is_ascii_digit()).len()
+            }
+            _ => 0,
+        };
+        if int + frac == 0 {
+            self.reset(m);
+            return None;
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let before_exp = self.mark();
+            self.i += 1;
+            let _ = self.skip_byte(b'+').or_else(|| self.skip_byte(b'-'));
+            if self.take_int_ascii().is_none() {
+                self.reset(before_exp);
+            }
+        }
+        Some(m..self.i)
+    }
+
+    /// Parses a dotted-quad IPv4 address. Each octet is 1–3 digits no greater
+    /// than 255. Rolls back fully on any failure.
+    pub fn parse_ipv4(&mut self) -> Option<[u8; 4]> {
+        let m = self.mark();
+        let mut octets = [0u8; 4];
+        for (k, octet) in octets.iter_mut().enumerate() {
+            let parsed = (k == 0 || self.skip_byte(b'.').is_some())
+                .then(|| self.take_int_ascii())
+                .flatten()
+                .filter(|span| span.len() <= 3)
+                .and_then(|span| {
+                    let n = self.buf[span]
+                        .iter()
+                        .fold(0u16, |acc, &d| acc * 10 + (d - b'0') as u16);
+                    u8::try_from(n).ok()
+                });
+            match parsed {
+                Some(n) => *octet = n,
+                None => {
+                    self.reset(m);
+                    return None;
+                }
+            }
+        }
+        Some(octets)
+    }
+
+    /// Parses up to `max_parts` integers separated by `sep` (e.g. `1.2.3`),
+    /// stopping before a `sep` that isn't followed by a digit. Rolls back on
+    /// overflow or when no integer is present.
+    pub fn take_dotted_ints(&mut self, sep: u8, max_parts: usize) -> Option<Vec<u64>> {
+        let mut parts = Vec::new();
+        self.take_dotted_with(sep, max_parts, |_, n| parts.push(n))?;
+        Some(parts)
+    }
+    /// Like [`take_dotted_ints`](Self::take_dotted_ints) but writes into `out`,
+    /// parsing at most `out.len()` parts and returning how many were written.
+    /// On `None` the cursor is rolled back, but `out` may already hold the
+    /// parts parsed before the failure.
+    pub fn take_dotted_ints_into(&mut self, sep: u8, out: &mut [u64]) -> Option<usize> {
+        let max_parts = out.len();
+        self.take_dotted_with(sep, max_parts, |idx, n| out[idx] = n)
+    }
+    fn take_dotted_with(
+        &mut self,
+        sep: u8,
+        max_parts: usize,
+        mut emit: impl FnMut(usize, u64),
+    ) -> Option<usize> {
+        let m = self.mark();
+        let mut count = 0;
+        while count < max_parts {
+            let before_sep = self.mark();
+            if count > 0
+                && (self.skip_byte(sep).is_none()
+                    || !self.peek().is_some_and(|b| b.is_ascii_digit()))
+            {
+                self.reset(before_sep);
+                break;
+            }
+            let Some(n) = self.parse_u64_ascii() else {
+                self.reset(m);
+                return None;
+            };
+            emit(count, n);
+            count += 1;
+        }
+        (count > 0).then_some(count)
     }
 
     // Predicate-based
     #[inline]
     pub fn skip_while(&mut self, mut pred: impl FnMut(u8) -> bool) -> Range<usize> {
         let start = self.i;
+        let limit = self.scan_limit();
+        let mut cut = false;
         while let Some(&b) = self.buf.get(self.i) {
             if !pred(b) {
                 break;
             }
+            if self.i == limit {
+                cut = true;
+                break;
+            }
             self.i += 1;
         }
+        self.charge_scan(start, cut);
         start..self.i
     }
     #[inline]
-    pub fn take_while(&mut self, mut pred: impl FnMut(u8) -> bool) -> Option<Range<usize>> {
+    pub fn take_while(&mut self, pred: impl FnMut(u8) -> bool) -> Option<Range<usize>> {
+        let span = Cursor::skip_while(self, pred);
+        if span.is_empty() { None } else { Some(span) }
+    }
+    #[inline]
+    pub fn skip_while_result(&mut self, mut pred: impl FnMut(u8) -> ControlFlow<()>) -> usize {
+        Cursor::skip_while(self, |b| pred(b).is_continue()).len()
+    }
+    /// Consumes between `min` and `max` bytes from `set`, stopping at `max`.
+    /// Returns `None` without consuming if fewer than `min` (or no) bytes match.
+    #[inline]
+    pub fn take_set_bounded(
+        &mut self,
+        set: &ByteSet,
+        min: usize,
+        max: usize,
+    ) -> Option<Range<usize>> {
+        self.within_budget(|c| {
+            let span = c.with_limit(max, |c| c.take_while(|b| set.contains(b)))?;
+            if span.len() < min {
+                c.i = span.start;
+                return None;
+            }
+            Some(span)
+        })
+    }
+    #[inline]
+    pub fn take_ranges(&mut self, ranges: &[(u8, u8)]) -> Option<Range<usize>> {
+        self.within_budget(|c| {
+            c.take_while(|b| ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&b)))
+        })
+    }
+    #[inline]
+    pub fn skip_while_pair(&mut self, mut pred: impl FnMut(u8, Option<u8>) -> bool) -> usize {
         let start = self.i;
+        let limit = self.scan_limit();
+        let mut cut = false;
         while let Some(&b) = self.buf.get(self.i) {
-            if !pred(b) {
+            if !pred(b, self.peek_n(1)) {
+                break;
+            }
+            if self.i == limit {
+                cut = true;
                 break;
             }
             self.i += 1;
         }
-        if self.i > start {
-            Some(start..self.i)
-        } else {
-            None
+        self.charge_scan(start, cut);
+        self.i - start
+    }
+    /// Consumes bytes while `f` maps them to `Some`, collecting the values. The
+    /// first byte mapped to `None` is left unconsumed.
+    pub fn take_while_map<T>(&mut self, mut f: impl FnMut(u8) -> Option<T>) -> Vec<T> {
+        let mut out = Vec::new();
+        let start = self.i;
+        let limit = self.scan_limit();
+        let mut cut = false;
+        while let Some(v) = self.peek().and_then(&mut f) {
+            if self.i == limit {
+                cut = true;
+                break;
+            }
+            out.push(v);
+            self.i += 1;
+        }
+        self.charge_scan(start, cut);
+        out
+    }
+    #[inline]
+    pub fn for_each_while(
+        &mut self,
+        mut pred: impl FnMut(u8) -> bool,
+        mut sink: impl FnMut(u8),
+    ) -> usize {
+        let start = self.i;
+        let limit = self.scan_limit();
+        let mut cut = false;
+        while let Some(&b) = self.buf.get(self.i) {
+            if !pred(b) {
+                break;
+            }
+            if self.i == limit {
+                cut = true;
+                break;
+            }
+            sink(b);
+            self.i += 1;
         }
+        self.charge_scan(start, cut);
+        self.i - start
+    }
+    pub fn skip_while_dyn(&mut self, pred: &mut dyn FnMut(u8) -> bool) -> usize {
+        self.skip_while(pred).len()
+    }
+    pub fn take_while_dyn(&mut self, pred: &mut dyn FnMut(u8) -> bool) -> Option<Range<usize>> {
+        self.take_while(pred)
+    }
+    #[inline]
+    pub fn take_while_slice(&mut self, pred: impl FnMut(u8) -> bool) -> Option<&'a [u8]> {
+        let span = self.take_while(pred)?;
+        Some(&self.buf[span])
     }
 
     // Single-byte expectation with rollback
@@ -207,16 +1461,455 @@ impl<'a> Cursor<'a> {
             None
         }
     }
+    #[inline]
+    pub fn take_run(&mut self, b: u8) -> Option<Range<usize>> {
+        self.within_budget(|c| c.take_while(|x| x == b))
+    }
+    #[inline]
+    pub fn expect_run(&mut self, b: u8, min: usize, max: usize) -> Option<Range<usize>> {
+        self.within_budget(|c| {
+            let span = c.with_limit(max, |c| c.skip_while(|x| x == b));
+            if span.len() < min {
+                c.i = span.start;
+                return None;
+            }
+            Some(span)
+        })
+    }
 
     // Prefix/slice peeking
     #[inline]
     pub fn starts_with(&self, pat: &[u8]) -> bool {
         self.buf[self.i..].starts_with(pat)
     }
+    /// Returns exactly the next `n` bytes, or `None` if fewer remain. Never
+    /// panics, even for `n` near `usize::MAX`.
     #[inline]
     pub fn peek_slice(&self, n: usize) -> Option<&'a [u8]> {
-        self.buf.get(self.i..self.i + n)
+        self.buf.get(self.i..self.i.checked_add(n)?)
+    }
+    /// Returns up to `n` of the next bytes, fewer near EOF (possibly empty).
+    #[inline]
+    pub fn peek_slice_upto(&self, n: usize) -> &'a [u8] {
+        let rest = self.as_slice();
+        &rest[..n.min(rest.len())]
+    }
+    #[inline]
+    pub fn peek_tail(&self, n: usize) -> Option<&'a [u8]> {
+        let rest = self.as_slice();
+        rest.get(rest.len().checked_sub(n)?..)
+    }
+    #[inline]
+    pub fn peek_array<const N: usize>(&self) -> Option<[u8; N]> {
+        self.peek_slice(N)?.try_into().ok()
+    }
+
+    // Transforms
+    /// Consumes the rest of the buffer into `dst`, converting `\r\n` and lone
+    /// `\r` to `\n`.
+    pub fn copy_normalized_newlines(&mut self, dst: &mut Vec<u8>) {
+        dst.reserve(self.remaining());
+        while let Some(b) = self.next_byte() {
+            if b == b'\r' {
+                self.skip_byte(b'\n');
+                dst.push(b'\n');
+            } else {
+                dst.push(b);
+            }
+        }
+    }
+    /// Unescapes into `dst` up to (not including) `delim`. Each `esc` and the
+    /// byte after it are replaced by `table[byte]`, or copied literally when
+    /// that entry is `None`. Returns whether `delim` was reached.
+    pub fn decode_with_table(
+        &mut self,
+        delim: u8,
+        esc: u8,
+        table: &[Option<u8>; 256],
+        dst: &mut Vec<u8>,
+    ) -> bool {
+        loop {
+            let rest = self.as_slice();
+            let run = rest
+                .iter()
+                .position(|&b| b == delim || b == esc)
+                .unwrap_or(rest.len());
+            dst.extend_from_slice(&rest[..run]);
+            self.i += run;
+            match self.peek() {
+                None => return false,
+                Some(b) if b == delim => return true,
+                Some(_) => self.i += 1,
+            }
+            match self.next_byte() {
+                Some(b) => match table[b as usize] {
+                    Some(decoded) => dst.push(decoded),
+                    None => dst.extend_from_slice(&[esc, b]),
+                },
+                None => {
+                    dst.push(esc);
+                    return false;
+                }
+            }
+        }
+    }
+
+    // Text extraction
+    /// Consumes up to `n` bytes and decodes them lossily, borrowing when the
+    /// bytes are valid UTF-8.
+    #[inline]
+    pub fn take_str_lossy(&mut self, n: usize) -> Cow<'a, str> {
+        let start = self.i;
+        self.i += n.min(self.remaining());
+        String::from_utf8_lossy(&self.buf[start..self.i])
+    }
+
+    #[inline]
+    pub fn consume_str(&mut self, n: usize) -> Option<&'a str> {
+        let s = core::str::from_utf8(self.peek_slice(n)?).ok()?;
+        self.i += n;
+        Some(s)
+    }
+    /// Returns the remaining buffer as `&str` without validating it.
+    ///
+    /// # Safety
+    ///
+    /// The remaining bytes must be valid UTF-8, e.g. because they were already
+    /// scanned as ASCII. Debug builds assert this.
+    #[inline]
+    pub unsafe fn remaining_str_unchecked(&self) -> &'a str {
+        // SAFETY: the caller guarantees the bytes are valid UTF-8.
+        unsafe { self.str_of_unchecked(self.i..self.buf.len()) }
+    }
+    /// Returns `span` of the buffer as `&str` without validating it.
+    ///
+    /// # Safety
+    ///
+    /// `span` must lie within the buffer and cover valid UTF-8. Debug builds
+    /// assert the UTF-8 requirement; an out-of-range span panics.
+    #[inline]
+    pub unsafe fn str_of_unchecked(&self, span: Range<usize>) -> &'a str {
+        let bytes = &self.buf[span];
+        debug_assert!(
+            core::str::from_utf8(bytes).is_ok(),
+            "span is not valid UTF-8"
+        );
+        // SAFETY: the caller guarantees the bytes are valid UTF-8.
+        unsafe { core::str::from_utf8_unchecked(bytes) }
     }
+    /// Counts chars in the remaining buffer. Each invalid UTF-8 sequence counts
+    /// as one char, matching the `U+FFFD` replacements of
+    /// `String::from_utf8_lossy`.
+    pub fn char_count_remaining(&self) -> usize {
+        self.as_slice()
+            .utf8_chunks()
+            .map(|chunk| chunk.valid().chars().count() + usize::from(!chunk.invalid().is_empty()))
+            .sum()
+    }
+    #[inline]
+    pub fn first_utf8_error(&self) -> Option<usize> {
+        core::str::from_utf8(self.as_slice())
+            .err()
+            .map(|e| self.i + e.valid_up_to())
+    }
+
+    // Offset conversion
+    /// Returns the number of UTF-8 chars that start before `byte_pos` (clamped
+    /// to the buffer). A position inside a multibyte char rounds up, counting
+    /// that char as already passed.
+    #[inline]
+    pub fn char_offset_of(&self, byte_pos: usize) -> usize {
+        let end = byte_pos.min(self.buf.len());
+        self.buf[..end]
+            .iter()
+            .filter(|&&b| !is_utf8_continuation(b))
+            .count()
+    }
+    /// Returns the byte offset at which char `char_idx` starts, or the buffer
+    /// length when `char_idx` equals the char count. `None` past the end.
+    #[inline]
+    pub fn byte_offset_of_char(&self, char_idx: usize) -> Option<usize> {
+        let mut starts = self
+            .buf
+            .iter()
+            .enumerate()
+            .filter(|&(_, &b)| !is_utf8_continuation(b))
+            .map(|(pos, _)| pos)
+            .chain(core::iter::once(self.buf.len()));
+        starts.nth(char_idx)
+    }
+
+    // Binary fields
+    /// Consumes a NUL-terminated string, returning the span before the `\0`
+    /// and leaving the cursor after it. `None` if no terminator is found.
+    #[inline]
+    pub fn take_cstr(&mut self) -> Option<Range<usize>> {
+        let off = self.as_slice().iter().position(|&b| b == 0)?;
+        let content = self.i..self.i + off;
+        self.i += off + 1;
+        Some(content)
+    }
+    /// Compares the input against `magic`, consuming it only on
+    /// [`MagicResult::Match`].
+    pub fn check_magic(&mut self, magic: &[u8]) -> MagicResult {
+        let hay = self.as_slice();
+        match hay.iter().zip(magic).position(|(a, b)| a != b) {
+            Some(k) => MagicResult::Mismatch {
+                at: self.i + k,
+                expected: magic[k],
+                found: hay[k],
+            },
+            None if hay.len() < magic.len() => MagicResult::Truncated { matched: hay.len() },
+            None => {
+                self.i += magic.len();
+                MagicResult::Match
+            }
+        }
+    }
+    /// Reads a `len_bytes`-wide unsigned length field at the current position
+    /// without advancing. `None` if the field is truncated, wider than
+    /// `usize`, or `len_bytes` is zero.
+    #[inline]
+    pub fn peek_tlv_len(&self, len_bytes: usize, big_endian: bool) -> Option<usize> {
+        if len_bytes == 0 || len_bytes > size_of::<usize>() {
+            return None;
+        }
+        let field = self.peek_slice(len_bytes)?;
+        let fold = |acc: usize, &b: &u8| acc << 8 | b as usize;
+        Some(if big_endian {
+            field.iter().fold(0, fold)
+        } else {
+            field.iter().rev().fold(0, fold)
+        })
+    }
+
+    // Debugging
+    /// Renders `span` (clamped to the buffer) as a classic hex dump: absolute
+    /// offset, 16 hex bytes, and their printable ASCII.
+    pub fn hexdump_span(&self, span: Range<usize>) -> String {
+        self.hexdump_rows(span, None)
+    }
+    /// Dumps up to `radius` bytes on each side of the current position,
+    /// marking the current byte with `^^` on the line below its row.
+    pub fn hexdump_around(&self, radius: usize) -> String {
+        let span = self.i.saturating_sub(radius)..self.i.saturating_add(radius).saturating_add(1);
+        self.hexdump_rows(span, Some(self.i))
+    }
+    fn hexdump_rows(&self, span: Range<usize>, marker: Option<usize>) -> String {
+        let end = span.end.min(self.buf.len());
+        let start = span.start.min(end);
+        let mut out = String::new();
+        for (row, chunk) in self.buf[start..end].chunks(16).enumerate() {
+            let offset = start + row * 16;
+            let _ = write!(out, "{offset:08x} ");
+            for k in 0..16 {
+                if k == 8 {
+                    out.push(' ');
+                }
+                match chunk.get(k) {
+                    Some(b) => {
+                        let _ = write!(out, " {b:02x}");
+                    }
+                    None => out.push_str("   "),
+                }
+            }
+            out.push_str("  |");
+            out.extend(chunk.iter().map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            }));
+            out.push_str("|\n");
+            if let Some(k) = marker
+                .and_then(|m| m.checked_sub(offset))
+                .filter(|&k| k < chunk.len())
+            {
+                let col = 10 + k * 3 + usize::from(k >= 8);
+                let _ = writeln!(out, "{:col$}^^", "");
+            }
+        }
+        out
+    }
+
+    // Tokenization
+    /// Yields tokens produced by `T::next_token`. Iteration ends when it returns
+    /// `None` or returns a token without advancing the cursor.
+    pub fn tokenize<T: Tokenize>(mut self) -> impl Iterator<Item = T::Token> {
+        core::iter::from_fn(move || {
+            let start = self.i;
+            let token = T::next_token(&mut self)?;
+            (self.i > start).then_some(token)
+        })
+    }
+
+    /// Reads a `len_bytes`-wide length field and then that many payload bytes.
+    /// Consumes nothing if the field or the payload is truncated.
+    #[inline]
+    pub fn read_length_prefixed(&mut self, len_bytes: usize, big_endian: bool) -> Option<&'a [u8]> {
+        let len = self.peek_tlv_len(len_bytes, big_endian)?;
+        let payload = self.buf.get(self.i + len_bytes..)?.get(..len)?;
+        self.i += len_bytes + len;
+        Some(payload)
+    }
+
+    /// Yields successive `size`-byte records, advancing past each as it is
+    /// yielded. Like [`chunks_exact`](slice::chunks_exact), a trailing partial
+    /// record is not yielded and stays in [`remaining`](Self::remaining).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn chunks(&mut self, size: usize) -> impl Iterator<Item = &'a [u8]> {
+        assert!(size != 0, "chunk size must be non-zero");
+        core::iter::from_fn(move || {
+            let chunk = self.peek_slice(size)?;
+            self.i += size;
+            Some(chunk)
+        })
+    }
+
+    // Content-defined chunking
+    /// Polynomial base of the rolling hash used by
+    /// [`advance_until_rolling_boundary`](Self::advance_until_rolling_boundary).
+    pub const ROLLING_HASH_BASE: u64 = 0x0100_0000_01B3;
+
+    /// Advances until the rolling hash of the last `window` bytes satisfies
+    /// `hash & mask == 0`, or to EOF, returning the consumed span (which ends
+    /// just after the boundary byte).
+    ///
+    /// The hash of a window `b[0..w]` is `sum(b[k] * BASE^(w-1-k))` with
+    /// wrapping `u64` arithmetic and `BASE` = [`Self::ROLLING_HASH_BASE`]. It is
+    /// only tested once a full window has been seen.
+    pub fn advance_until_rolling_boundary(&mut self, window: usize, mask: u64) -> Range<usize> {
+        let window = window.max(1);
+        let base = Self::ROLLING_HASH_BASE;
+        let out_factor = (1..window).fold(1u64, |acc, _| acc.wrapping_mul(base));
+        let start = self.i;
+        let mut hash = 0u64;
+        while let Some(b) = self.next_byte() {
+            let seen = self.i - start;
+            if seen > window {
+                let out = self.buf[self.i - 1 - window] as u64;
+                hash = hash.wrapping_sub(out.wrapping_mul(out_factor));
+            }
+            hash = hash.wrapping_mul(base).wrapping_add(b as u64);
+            if seen >= window && hash & mask == 0 {
+                break;
+            }
+        }
+        start..self.i
+    }
+
+    // Table-driven dispatch
+    #[inline]
+    pub fn classify_next<T: Copy>(&self, table: &[Option<T>; 256]) -> Option<T> {
+        table[self.peek()? as usize]
+    }
+    #[inline]
+    pub fn current_class(&self, table: &[u8; 256]) -> Option<u8> {
+        Some(table[self.peek()? as usize])
+    }
+
+    /// Finds the first class in `classes` containing the current byte and
+    /// returns its tag with the length of the leading run of that class,
+    /// without advancing.
+    pub fn peek_token_class(&self, classes: &[(&ByteSet, u32)]) -> Option<(u32, usize)> {
+        let b = self.peek()?;
+        let &(set, tag) = classes.iter().find(|(set, _)| set.contains(b))?;
+        let len = self
+            .as_slice()
+            .iter()
+            .take_while(|&&x| set.contains(x))
+            .count();
+        Some((tag, len))
+    }
+
+    // Human-readable units
+    /// Parses an unsigned integer followed by a size unit, returning the size
+    /// in bytes. Decimal units (`B`, `kB`/`KB`, `MB`, `GB`, `TB`) scale by powers
+    /// of 1000; binary units (`KiB`, `MiB`, `GiB`, `TiB`) scale by powers of 1024.
+    /// Units are case-sensitive and must not be followed by an identifier byte.
+    /// Rolls back on a missing unit or overflow.
+    #[cfg(feature = "units")]
+    pub fn parse_byte_size(&mut self) -> Option<u64> {
+        const UNITS: &[(&[u8], u64)] = &[
+            (b"KiB", 1 << 10),
+            (b"MiB", 1 << 20),
+            (b"GiB", 1 << 30),
+            (b"TiB", 1 << 40),
+            (b"kB", 1_000),
+            (b"KB", 1_000),
+            (b"MB", 1_000_000),
+            (b"GB", 1_000_000_000),
+            (b"TB", 1_000_000_000_000),
+            (b"B", 1),
+        ];
+        self.parse_with_unit(UNITS)
+    }
+
+    /// Parses an unsigned integer followed by a duration unit, returning the
+    /// duration in milliseconds. Accepted units are `ms`, `s`, `m`, `h` and
+    /// `d`; they are case-sensitive and must not be followed by an identifier
+    /// byte. Rolls back on a missing unit or overflow.
+    #[cfg(feature = "units")]
+    pub fn parse_duration_ms(&mut self) -> Option<u64> {
+        const UNITS: &[(&[u8], u64)] = &[
+            (b"ms", 1),
+            (b"s", 1_000),
+            (b"m", 60_000),
+            (b"h", 3_600_000),
+            (b"d", 86_400_000),
+        ];
+        self.parse_with_unit(UNITS)
+    }
+
+    #[cfg(feature = "units")]
+    fn parse_with_unit(&mut self, units: &[(&[u8], u64)]) -> Option<u64> {
+        let m = self.mark();
+        let value = self.parse_u64_ascii().and_then(|n| {
+            let &(unit, scale) = units.iter().find(|(unit, _)| {
+                self.starts_with(unit)
+                    && !self
+                        .peek_n(unit.len())
+                        .is_some_and(Self::is_ident_continue_ascii)
+            })?;
+            self.i += unit.len();
+            n.checked_mul(scale)
+        });
+        if value.is_none() {
+            self.reset(m);
+        }
+        value
+    }
+}
+
+/// Outcome of [`Cursor::check_magic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MagicResult {
+    Match,
+    /// The byte at absolute position `at` differs from the magic.
+    Mismatch {
+        at: usize,
+        expected: u8,
+        found: u8,
+    },
+    /// The input ended after `matched` bytes of the magic.
+    Truncated {
+        matched: usize,
+    },
+}
+
+/// A token rule that can be driven by [`Cursor::tokenize`].
+pub trait Tokenize {
+    type Token;
+    fn next_token(c: &mut Cursor<'_>) -> Option<Self::Token>;
+}
+
+#[inline]
+const fn is_utf8_continuation(b: u8) -> bool {
+    b & 0xC0 == 0x80
 }
 
 impl<'a> Iterator for Cursor<'a> {
@@ -226,3 +1919,13 @@ impl<'a> Iterator for Cursor<'a> {
         self.next_byte()
     }
 }
+
+impl std::io::Read for Cursor<'_> {
+    #[inline]
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        let n = out.len().min(self.remaining());
+        out[..n].copy_from_slice(&self.buf[self.i..self.i + n]);
+        self.i += n;
+        Ok(n)
+    }
+}
@@ -1,2 +1,6 @@
+pub mod byteset;
 pub mod cursor;
-pub use cursor::Cursor;
\ No newline at end of file
+#[cfg(feature = "trace")]
+pub mod trace;
+pub use byteset::ByteSet;
+pub use cursor::{Cursor, MagicResult, Tokenize};
\ No newline at end of file
@@ -0,0 +1,42 @@
+/// Number of events retained by a cursor's trace log.
+pub const CAPACITY: usize = 64;
+/// `advance` calls of at least this many bytes are recorded.
+pub const LARGE_ADVANCE: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceEvent {
+    Mark { pos: usize },
+    /// A mark taken with [`Cursor::mark_named`](crate::Cursor::mark_named).
+    NamedMark { pos: usize, label: &'static str },
+    Reset { from: usize, to: usize },
+    Advance { from: usize, to: usize },
+}
+
+/// Ring buffer holding the most recent [`CAPACITY`] events.
+#[derive(Debug)]
+pub(crate) struct TraceLog {
+    events: [Option<TraceEvent>; CAPACITY],
+    next: usize,
+}
+
+impl TraceLog {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Self {
+            events: [None; CAPACITY],
+            next: 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn record(&mut self, event: TraceEvent) {
+        self.events[self.next] = Some(event);
+        self.next = (self.next + 1) % CAPACITY;
+    }
+
+    /// Events from oldest to newest.
+    pub(crate) fn events(&self) -> Vec<TraceEvent> {
+        let (newer, older) = self.events.split_at(self.next);
+        older.iter().chain(newer).flatten().copied().collect()
+    }
+}
@@ -99,3 +99,1716 @@ fn mark_and_reset() {
     assert_eq!(c.pos(), 2);
     assert_eq!(c.peek(), Some(b'3'));
 }
+
+#[test]
+fn take_while_slice_returns_bytes() {
+    let mut c = Cursor::new(b"abc123");
+    assert_eq!(
+        c.take_while_slice(|b| b.is_ascii_alphabetic()),
+        Some(&b"abc"[..])
+    );
+    assert_eq!(c.pos(), 3);
+    assert_eq!(c.take_while_slice(|b| b == b'x'), None);
+    assert_eq!(c.pos(), 3);
+}
+
+#[test]
+fn classify_next_uses_table() {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum TokenKind {
+        Open,
+        Close,
+        Digit,
+    }
+
+    let mut table = [None; 256];
+    table[b'{' as usize] = Some(TokenKind::Open);
+    table[b'}' as usize] = Some(TokenKind::Close);
+    for d in b'0'..=b'9' {
+        table[d as usize] = Some(TokenKind::Digit);
+    }
+
+    let mut c = Cursor::new(b"{7}x");
+    assert_eq!(c.classify_next(&table), Some(TokenKind::Open));
+    assert_eq!(c.pos(), 0); // not advanced
+    c.next_byte();
+    assert_eq!(c.classify_next(&table), Some(TokenKind::Digit));
+    c.next_byte();
+    assert_eq!(c.classify_next(&table), Some(TokenKind::Close));
+    c.next_byte();
+    assert_eq!(c.classify_next(&table), None);
+    c.next_byte();
+    assert!(c.eof());
+    assert_eq!(c.classify_next(&table), None);
+}
+
+#[test]
+fn skip_until_found_reports_delimiter() {
+    let mut c = Cursor::new(b"key=value");
+    assert_eq!(c.skip_until_found(b'='), (3, true));
+    assert_eq!(c.peek(), Some(b'='));
+
+    let mut d = Cursor::new(b"no delimiter");
+    let remaining = d.remaining();
+    assert_eq!(d.skip_until_found(b'='), (remaining, false));
+    assert!(d.eof());
+}
+
+#[cfg(feature = "units")]
+#[test]
+fn parse_units() {
+    let mut c = Cursor::new(b"4KiB 2MB 7");
+    assert_eq!(c.parse_byte_size(), Some(4096));
+    c.take_space();
+    assert_eq!(c.parse_byte_size(), Some(2_000_000));
+    c.take_space();
+    let m = c.mark();
+    assert_eq!(c.parse_byte_size(), None); // unit is required
+    assert_eq!(c.pos(), m);
+
+    let mut d = Cursor::new(b"500ms 5s 3sec");
+    assert_eq!(d.parse_duration_ms(), Some(500));
+    d.take_space();
+    assert_eq!(d.parse_duration_ms(), Some(5_000));
+    d.take_space();
+    let m = d.mark();
+    assert_eq!(d.parse_duration_ms(), None); // `s` followed by identifier bytes
+    assert_eq!(d.pos(), m);
+}
+
+#[test]
+fn region_rescans_consumed_span() {
+    let mut c = Cursor::new(b"abc def");
+    let word = c.take_ident_ascii().expect("ident");
+    c.take_space();
+
+    let mut r = c.region(word.start, word.end);
+    assert_eq!(r.len(), 3);
+    assert_eq!(r.next_byte(), Some(b'a'));
+    assert_eq!(r.next_byte(), Some(b'b'));
+    assert_eq!(r.next_byte(), Some(b'c'));
+    assert_eq!(r.next_byte(), None);
+    assert_eq!(c.pos(), 4); // main cursor untouched
+
+    // Out-of-range bounds are clamped; inverted bounds yield an empty region.
+    assert_eq!(c.region(5, 100).len(), 2);
+    assert!(c.region(6, 2).is_empty());
+}
+
+#[test]
+fn find_subslice_does_not_advance() {
+    let mut c = Cursor::new(b"GET /index.html HTTP/1.1\r\n\r\n");
+    c.advance(4);
+    assert_eq!(c.find_subslice(b"\r\n\r\n"), Some(24));
+    assert_eq!(c.find_subslice(b"HTTP"), Some(16));
+    assert_eq!(c.find_subslice(b"GET"), None); // only the remaining buffer is searched
+    assert_eq!(c.find_subslice(b""), Some(c.pos()));
+    assert_eq!(c.pos(), 4);
+}
+
+#[test]
+fn expect_indent_is_exact() {
+    let mut c = Cursor::new(b"    body");
+    assert_eq!(c.expect_indent(4), Some(0..4));
+    assert_eq!(c.peek(), Some(b'b'));
+
+    let mut few = Cursor::new(b"  body");
+    assert_no_span(few.expect_indent(4));
+    assert_eq!(few.pos(), 0);
+
+    let mut more = Cursor::new(b"      body");
+    assert_no_span(more.expect_indent(4));
+    assert_eq!(more.pos(), 0);
+
+    let mut tab = Cursor::new(b"  \t  body");
+    assert_no_span(tab.expect_indent(2));
+    assert_no_span(tab.expect_indent(4));
+    assert_eq!(tab.pos(), 0);
+}
+
+#[test]
+fn skip_block_comment_terminated_and_not() {
+    let mut c = Cursor::new(b"/* note */x");
+    assert!(c.skip_block_comment(b"/*", b"*/", false));
+    assert_eq!(c.peek(), Some(b'x'));
+
+    let mut strict = Cursor::new(b"/* runs off the end");
+    assert!(!strict.skip_block_comment(b"/*", b"*/", false));
+    assert_eq!(strict.pos(), 0); // rolled back to the opener
+
+    let mut lenient = Cursor::new(b"/* runs off the end");
+    assert!(lenient.skip_block_comment(b"/*", b"*/", true));
+    assert!(lenient.eof());
+
+    let mut none = Cursor::new(b"x /* */");
+    assert!(!none.skip_block_comment(b"/*", b"*/", true));
+    assert_eq!(none.pos(), 0);
+}
+
+#[test]
+fn char_and_byte_offsets_round_trip() {
+    let input = "aé€b".as_bytes(); // 1 + 2 + 3 + 1 bytes
+    let c = Cursor::new(input);
+    for (char_idx, (byte_pos, _)) in "aé€b".char_indices().enumerate() {
+        assert_eq!(c.byte_offset_of_char(char_idx), Some(byte_pos));
+        assert_eq!(c.char_offset_of(byte_pos), char_idx);
+    }
+    assert_eq!(c.byte_offset_of_char(4), Some(input.len()));
+    assert_eq!(c.byte_offset_of_char(5), None);
+    assert_eq!(c.char_offset_of(input.len()), 4);
+
+    // Inside `€` (bytes 3..6) rounds up past it.
+    assert_eq!(c.char_offset_of(4), 3);
+}
+
+#[test]
+fn take_arg_respects_nesting() {
+    let input = b"f(a, g(b, c), d)";
+    let mut c = Cursor::new(input);
+    c.take_ident_ascii();
+    assert!(c.expect_byte(b'(').is_some());
+
+    let mut args = Vec::new();
+    loop {
+        let arg = c.take_arg(b',', b'(', b')');
+        args.push(&input[arg]);
+        if c.expect_byte(b',').is_none() {
+            break;
+        }
+    }
+    assert_eq!(args, [&b"a"[..], b"g(b, c)", b"d"]);
+    assert_eq!(c.peek(), Some(b')'));
+}
+
+#[test]
+fn new_at_end_reads_backwards() {
+    let mut c = Cursor::new_at_end(b"PK\x05\x06");
+    assert!(c.eof());
+    assert_eq!(c.pos(), 4);
+    assert_eq!(c.peek(), None);
+
+    assert_eq!(c.prev_byte(), Some(0x06));
+    assert_eq!(c.pos(), 3);
+    assert_eq!(c.rewind(3), Some(0..3));
+    assert_eq!(c.prev_byte(), None);
+    assert_no_span(c.rewind(1));
+    assert_eq!(c.peek(), Some(b'P'));
+}
+
+#[test]
+fn expect_run_bounds() {
+    let mut fence = Cursor::new(b"```rust");
+    assert_eq!(fence.expect_run(b'`', 3, usize::MAX), Some(0..3));
+    assert_eq!(fence.peek(), Some(b'r'));
+
+    let mut short = Cursor::new(b"``x");
+    assert_no_span(short.expect_run(b'`', 3, usize::MAX));
+    assert_eq!(short.pos(), 0);
+
+    let mut heading = Cursor::new(b"########");
+    assert_eq!(heading.expect_run(b'#', 1, 6), Some(0..6));
+    assert_eq!(heading.remaining(), 2);
+}
+
+#[test]
+fn estimate_splits_counts_delimiters() {
+    let mut c = Cursor::new(b"a,b,,c");
+    assert_eq!(c.count_byte(b','), 3);
+    assert_eq!(c.estimate_splits(b','), 4);
+    c.advance(2);
+    assert_eq!(c.estimate_splits(b','), 3);
+    assert_eq!(c.estimate_splits(b';'), 1);
+}
+
+#[test]
+fn skip_to_col_expands_tabs() {
+    let input = b"id\tname\tqty\nnext";
+    let mut c = Cursor::new(input);
+    assert!(c.skip_to_col(8, 4)); // "id" -> col 2, tab -> 4, "name" -> 8
+    assert_eq!(c.pos(), 7);
+    assert!(c.skip_to_col(12, 4)); // tab -> 12
+    assert_eq!(&input[c.pos()..c.pos() + 3], b"qty");
+
+    assert!(!c.skip_to_col(40, 4));
+    assert_eq!(c.peek(), Some(b'\n'));
+}
+
+#[test]
+fn peek_tlv_len_reads_without_consuming() {
+    let mut c = Cursor::new(b"\x01\x00\x03abc");
+    assert_eq!(c.next_byte(), Some(0x01)); // tag
+    assert_eq!(c.peek_tlv_len(2, true), Some(3));
+    assert_eq!(c.peek_tlv_len(2, false), Some(0x0300));
+    assert_eq!(c.pos(), 1);
+
+    let len = c.peek_tlv_len(2, true).unwrap();
+    assert!(c.remaining() >= 2 + len);
+    c.advance(2);
+    assert_eq!(c.peek_slice(len), Some(&b"abc"[..]));
+
+    assert_eq!(c.peek_tlv_len(8, true), None); // truncated
+    assert_eq!(c.peek_tlv_len(0, true), None);
+}
+
+#[test]
+fn copy_normalized_newlines_to_lf() {
+    let mut c = Cursor::new(b"a\r\nb\rc\nd\r");
+    let mut out = Vec::new();
+    c.copy_normalized_newlines(&mut out);
+    assert_eq!(out, b"a\nb\nc\nd\n");
+    assert!(c.eof());
+}
+
+#[test]
+fn match_keyword_any_enforces_boundary() {
+    let kws: &[&[u8]] = &[b"if", b"return"];
+
+    let mut c = Cursor::new(b"return x");
+    assert_eq!(c.match_keyword_any(kws), Some((1, 0..6)));
+
+    let mut d = Cursor::new(b"returns");
+    assert_eq!(d.match_keyword_any(kws), None);
+    assert_eq!(d.pos(), 0);
+
+    let mut e = Cursor::new(b"if");
+    assert_eq!(e.match_keyword_any(kws), Some((0, 0..2)));
+    assert!(e.eof());
+}
+
+#[test]
+fn scanner_at_spawns_independent_cursors() {
+    let parent = Cursor::new(b"left|right");
+    let mut a = parent.scanner_at(0);
+    let mut b = parent.scanner_at(5);
+
+    assert_eq!(a.skip_until(b'|'), 0..4);
+    assert_eq!(b.take_ident_ascii(), Some(5..10));
+    assert!(b.eof());
+    assert_eq!(a.pos(), 4);
+    assert_eq!(parent.pos(), 0);
+
+    assert!(parent.scanner_at(100).eof()); // clamped
+}
+
+#[test]
+fn take_dotted_ints_parses_versions() {
+    let mut c = Cursor::new(b"10.4.255-beta");
+    assert_eq!(c.take_dotted_ints(b'.', 8), Some(vec![10, 4, 255]));
+    assert_eq!(c.peek(), Some(b'-'));
+
+    let mut limited = Cursor::new(b"1.2.3");
+    assert_eq!(limited.take_dotted_ints(b'.', 2), Some(vec![1, 2]));
+    assert_eq!(limited.peek(), Some(b'.'));
+
+    let mut trailing = Cursor::new(b"1.2.");
+    let mut out = [0u64; 4];
+    assert_eq!(trailing.take_dotted_ints_into(b'.', &mut out), Some(2));
+    assert_eq!(out[..2], [1, 2]);
+    assert_eq!(trailing.remaining(), 1);
+
+    let mut overflow = Cursor::new(b"1.99999999999999999999");
+    assert_eq!(overflow.take_dotted_ints(b'.', 8), None);
+    assert_eq!(overflow.pos(), 0);
+
+    // `out` is written as parts are parsed, so a late failure leaves a prefix.
+    let mut overflow = Cursor::new(b"1.2.99999999999999999999");
+    let mut out = [0u64; 3];
+    assert_eq!(overflow.take_dotted_ints_into(b'.', &mut out), None);
+    assert_eq!(overflow.pos(), 0);
+    assert_eq!(out, [1, 2, 0]);
+}
+
+#[test]
+fn position_predicates() {
+    let mut c = Cursor::new(b"ab");
+    assert!(c.at_start());
+    assert!(!c.at_eof());
+    assert!(!c.past(0));
+
+    c.next_byte();
+    assert!(!c.at_start());
+    assert!(c.past(0));
+    assert!(!c.past(1));
+
+    c.next_byte();
+    assert!(c.at_eof());
+    assert!(c.past(1));
+
+    let empty = Cursor::new(b"");
+    assert!(empty.at_start() && empty.at_eof());
+}
+
+#[test]
+fn take_line_info_reports_terminator() {
+    let input = b"first\r\nsecond\nlast";
+    let mut c = Cursor::new(input);
+
+    let (line, terminated) = c.take_line_info().unwrap();
+    assert_eq!(&input[line], b"first");
+    assert!(terminated);
+
+    let (line, terminated) = c.take_line_info().unwrap();
+    assert_eq!(&input[line], b"second");
+    assert!(terminated);
+
+    let (line, terminated) = c.take_line_info().unwrap();
+    assert_eq!(&input[line], b"last");
+    assert!(!terminated);
+
+    assert!(c.take_line_info().is_none());
+}
+
+#[test]
+fn match_bytes_wild_ignores_wildcard_positions() {
+    // ELF magic followed by a class byte that may be 1 (32-bit) or 2 (64-bit).
+    let pat = b"\x7FELF?\x01";
+    let mut elf64 = Cursor::new(b"\x7FELF\x02\x01\x01");
+    assert_eq!(elf64.match_bytes_wild(pat, b'?'), Some(0..6));
+
+    let mut elf32 = Cursor::new(b"\x7FELF\x01\x01");
+    assert_eq!(elf32.match_bytes_wild(pat, b'?'), Some(0..6));
+
+    let mut big_endian = Cursor::new(b"\x7FELF\x02\x02");
+    assert_no_span(big_endian.match_bytes_wild(pat, b'?'));
+    assert_eq!(big_endian.pos(), 0);
+
+    let mut short = Cursor::new(b"\x7FEL");
+    assert_no_span(short.match_bytes_wild(pat, b'?'));
+}
+
+#[test]
+fn take_str_lossy_borrows_when_valid() {
+    use std::borrow::Cow;
+
+    let mut c = Cursor::new("héllo!".as_bytes());
+    let s = c.take_str_lossy(6);
+    assert!(matches!(s, Cow::Borrowed("héllo")));
+    assert_eq!(c.take_str_lossy(100), "!"); // clamped to remaining
+    assert!(c.eof());
+
+    let mut bad = Cursor::new(b"ok\xFFok");
+    let s = bad.take_str_lossy(5);
+    assert!(matches!(s, Cow::Owned(_)));
+    assert_eq!(s, "ok\u{FFFD}ok");
+}
+
+#[test]
+fn skip_while_result_two_state_scanner() {
+    use core::ops::ControlFlow;
+
+    // Skip to the first `;` that isn't inside a quoted string.
+    let mut c = Cursor::new(br#"a = "x;y"; b"#);
+    let mut in_string = false;
+    let skipped = c.skip_while_result(|b| match b {
+        b'"' => {
+            in_string = !in_string;
+            ControlFlow::Continue(())
+        }
+        b';' if !in_string => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    });
+    assert_eq!(skipped, 9);
+    assert_eq!(c.peek(), Some(b';'));
+}
+
+#[test]
+fn take_between_extracts_content() {
+    let input = b"<title>Hi</title>rest";
+    let mut c = Cursor::new(input);
+    let content = c.take_between(b"<title>", b"</title>").unwrap();
+    assert_eq!(&input[content], b"Hi");
+    assert_eq!(c.as_slice(), b"rest");
+
+    let mut unclosed = Cursor::new(b"<title>Hi");
+    assert_no_span(unclosed.take_between(b"<title>", b"</title>"));
+    assert_eq!(unclosed.pos(), 0);
+
+    let mut wrong_open = Cursor::new(b"<p>Hi</title>");
+    assert_no_span(wrong_open.take_between(b"<title>", b"</title>"));
+    assert_eq!(wrong_open.pos(), 0);
+}
+
+#[test]
+fn line_start_and_end_bound_the_line() {
+    let input = b"first\nsecond line\nthird";
+    let c = Cursor::new(input);
+    let pos = 10; // inside "second line"
+    let (start, end) = (c.line_start(pos), c.line_end(pos));
+    assert_eq!((start, end), (6, 17));
+    assert_eq!(&input[start..end], b"second line");
+
+    assert_eq!(c.line_start(3), 0);
+    assert_eq!(c.line_end(20), input.len());
+}
+
+#[test]
+fn take_run_spans_repeated_byte() {
+    let mut c = Cursor::new(b"###x");
+    assert_eq!(c.take_run(b'#'), Some(0..3));
+    assert_eq!(c.peek(), Some(b'x'));
+    assert_no_span(c.take_run(b'#'));
+}
+
+#[test]
+fn expect_exact_requires_whole_remainder() {
+    let mut c = Cursor::new(b"v=1.0");
+    c.advance(2);
+    assert!(c.expect_exact(b"1.0"));
+    assert!(c.eof());
+
+    let mut extra = Cursor::new(b"1.0\n");
+    assert!(!extra.expect_exact(b"1.0"));
+    assert_eq!(extra.pos(), 0);
+}
+
+#[test]
+fn advance_counting_lines_counts_newlines() {
+    let mut c = Cursor::new(b"a\nb\nc\nd");
+    assert_eq!(c.advance_counting_lines(4), 2);
+    assert_eq!(c.peek(), Some(b'c'));
+    assert_eq!(c.advance_counting_lines(100), 1); // clamped to EOF
+    assert!(c.eof());
+}
+
+#[test]
+fn peek_array_copies_without_advancing() {
+    let mut c = Cursor::new(b"\x89PNG\r\n");
+    assert_eq!(c.peek_array::<4>(), Some(*b"\x89PNG"));
+    assert_eq!(c.pos(), 0);
+
+    c.advance(3);
+    assert_eq!(c.peek_array::<4>(), None); // only 3 bytes left
+    assert_eq!(c.peek_array::<3>(), Some(*b"G\r\n"));
+}
+
+#[test]
+fn tokenize_with_trait_impl() {
+    use cursor_core::Tokenize;
+
+    #[derive(Debug, PartialEq)]
+    enum Tok {
+        Word(Range<usize>),
+        Num(Range<usize>),
+        Punct(u8),
+    }
+
+    struct Words;
+    impl Tokenize for Words {
+        type Token = Tok;
+        fn next_token(c: &mut Cursor<'_>) -> Option<Tok> {
+            c.take_space();
+            if let Some(span) = c.take_int_ascii() {
+                return Some(Tok::Num(span));
+            }
+            if let Some(span) = c.take_ident_starting_alpha() {
+                return Some(Tok::Word(span));
+            }
+            c.next_byte().map(Tok::Punct)
+        }
+    }
+
+    let toks: Vec<_> = Cursor::new(b"let x = 42;").tokenize::<Words>().collect();
+    assert_eq!(
+        toks,
+        [
+            Tok::Word(0..3),
+            Tok::Word(4..5),
+            Tok::Punct(b'='),
+            Tok::Num(8..10),
+            Tok::Punct(b';'),
+        ]
+    );
+
+    // A rule that never advances is stopped by the stall guard.
+    struct Stuck;
+    impl Tokenize for Stuck {
+        type Token = ();
+        fn next_token(_: &mut Cursor<'_>) -> Option<()> {
+            Some(())
+        }
+    }
+    assert_eq!(Cursor::new(b"abc").tokenize::<Stuck>().count(), 0);
+}
+
+#[test]
+fn first_utf8_error_locates_bad_byte() {
+    let mut c = Cursor::new(b"ab\xC3\xA9c\x80d");
+    assert_eq!(c.first_utf8_error(), Some(5)); // lone continuation byte
+    c.advance(6);
+    assert_eq!(c.first_utf8_error(), None);
+}
+
+#[test]
+fn take_digits_exact_fixed_width() {
+    let input = b"20240917";
+    let mut c = Cursor::new(input);
+    let year = c.take_digits_exact(4).unwrap();
+    assert_eq!(&input[year], b"2024");
+    assert_eq!(c.take_digits_exact(2), Some(4..6));
+    assert_eq!(c.take_digits_exact(2), Some(6..8));
+
+    let mut short = Cursor::new(b"202-");
+    assert_no_span(short.take_digits_exact(4));
+    assert_eq!(short.pos(), 0);
+}
+
+#[test]
+fn skip_space_report_detects_separator() {
+    let mut c = Cursor::new(b"a  b(c");
+    c.next_byte();
+    assert!(c.skip_space_report());
+    assert_eq!(c.peek(), Some(b'b'));
+    c.next_byte();
+    assert!(!c.skip_space_report()); // `b` and `(` are adjacent
+    assert_eq!(c.peek(), Some(b'('));
+}
+
+#[test]
+fn buffer_resolves_spans() {
+    let mut c = Cursor::new(b"  word");
+    c.take_space();
+    let span = c.take_ident_ascii().unwrap();
+    assert_eq!(&c.buffer()[span], b"word");
+    assert_eq!(c.buffer().len(), c.len());
+}
+
+#[test]
+fn split_once_around_delimiter() {
+    let input = b"Host: example.com";
+    let mut c = Cursor::new(input);
+    let (key, value) = c.split_once(b':').unwrap();
+    assert_eq!(&input[key], b"Host");
+    assert_eq!(&input[value], b" example.com");
+    assert!(c.eof());
+
+    let mut none = Cursor::new(b"no colon here");
+    assert!(none.split_once(b':').is_none());
+    assert_eq!(none.pos(), 0);
+}
+
+#[test]
+fn take_set_bounded_hex_digits() {
+    use cursor_core::ByteSet;
+
+    const HEX: ByteSet = ByteSet::from_range(b'0', b'9')
+        .union(ByteSet::from_range(b'a', b'f'))
+        .union(ByteSet::from_range(b'A', b'F'));
+    assert!(HEX.contains(b'c') && !HEX.contains(b'g'));
+
+    let mut c = Cursor::new(b"\\x1F;");
+    c.advance(2);
+    assert_eq!(c.take_set_bounded(&HEX, 2, 4), Some(2..4));
+    assert_eq!(c.peek(), Some(b';'));
+
+    let mut long = Cursor::new(b"deadbeef");
+    assert_eq!(long.take_set_bounded(&HEX, 2, 4), Some(0..4));
+    assert_eq!(long.as_slice(), b"beef");
+
+    let mut short = Cursor::new(b"a;");
+    assert_no_span(short.take_set_bounded(&HEX, 2, 4));
+    assert_eq!(short.pos(), 0);
+}
+
+#[test]
+fn reset_accepts_in_range_marks() {
+    let mut c = Cursor::new(b"abc");
+    c.advance(2);
+    c.reset(3);
+    assert!(c.eof());
+    c.reset(0);
+    assert_eq!(c.peek(), Some(b'a'));
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "out of range")]
+fn reset_out_of_range_mark_panics_in_debug() {
+    let mut c = Cursor::new(b"abc");
+    c.reset(10);
+}
+
+#[test]
+fn dyn_predicates_from_a_table() {
+    type Classifier = Box<dyn FnMut(u8) -> bool>;
+    let mut classifiers: Vec<(&str, Classifier)> = vec![
+        ("digits", Box::new(|b: u8| b.is_ascii_digit())),
+        ("alpha", Box::new(|b: u8| b.is_ascii_alphabetic())),
+    ];
+
+    let mut c = Cursor::new(b"123abc!");
+    assert_eq!(c.skip_while_dyn(&mut *classifiers[0].1), 3);
+    assert_eq!(c.take_while_dyn(&mut *classifiers[1].1), Some(3..6));
+    assert_no_span(c.take_while_dyn(&mut *classifiers[1].1));
+    assert_eq!(classifiers[0].0, "digits");
+}
+
+#[test]
+fn take_cstr_consumes_terminator() {
+    let input = b"abc\0rest";
+    let mut c = Cursor::new(input);
+    let s = c.take_cstr().unwrap();
+    assert_eq!(&input[s], b"abc");
+    assert_eq!(c.peek(), Some(b'r'));
+
+    assert_no_span(c.take_cstr()); // truncated
+    assert_eq!(c.pos(), 4);
+}
+
+#[test]
+fn with_limit_caps_reads_temporarily() {
+    let mut c = Cursor::new(b"\x03abcdef");
+    let len = c.next_byte().unwrap() as usize;
+    let body = c.with_limit(len, |inner| {
+        let span = inner.take_while(|b| b.is_ascii_alphabetic());
+        assert!(inner.eof());
+        assert_eq!(inner.peek(), None);
+        span
+    });
+    assert_eq!(body, Some(1..4));
+    assert!(!c.eof());
+    assert_eq!(c.as_slice(), b"def");
+}
+
+#[test]
+fn char_count_remaining_mixed() {
+    let mut c = Cursor::new("añ€😀".as_bytes());
+    assert_eq!(c.remaining(), 10);
+    assert_eq!(c.char_count_remaining(), 4);
+    c.advance(1);
+    assert_eq!(c.char_count_remaining(), 3);
+
+    let bad = Cursor::new(b"a\xFF\xFEb");
+    assert_eq!(
+        bad.char_count_remaining(),
+        String::from_utf8_lossy(bad.as_slice()).chars().count()
+    );
+}
+
+#[test]
+fn expect_alt_returns_index() {
+    let methods: &[&[u8]] = &[b"GET", b"POST", b"PUT"];
+
+    let mut c = Cursor::new(b"PUT /x");
+    assert_eq!(c.expect_alt(methods), Some(2));
+    assert_eq!(c.pos(), 3);
+
+    let mut d = Cursor::new(b"PATCH /x");
+    assert_eq!(d.expect_alt(methods), None);
+    assert_eq!(d.pos(), 0);
+}
+
+#[test]
+fn trim_span_strips_padding() {
+    use cursor_core::ByteSet;
+
+    let input = b"[**val**]";
+    let c = Cursor::new(input);
+    let stars = ByteSet::from_bytes(b"*");
+    let span = c.trim_span(1..8, &stars);
+    assert_eq!(&input[span], b"val");
+
+    let all = c.trim_span(1..3, &stars);
+    assert!(all.is_empty());
+}
+
+#[test]
+fn fork_and_adopt() {
+    let mut c = Cursor::new(b"let x");
+    let mut spec = c.fork();
+    assert!(spec.expect_bytes(b"let").is_some());
+    assert_eq!(c.pos(), 0); // original untouched until adoption
+    c.adopt(&spec);
+    assert_eq!(c.pos(), 3);
+
+    let mut failed = c.fork();
+    failed.take_space();
+    assert!(failed.expect_bytes(b"const").is_none());
+    // Not adopted: the original stays where it was.
+    assert_eq!(c.pos(), 3);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "different buffer")]
+fn adopt_from_other_buffer_panics_in_debug() {
+    let mut c = Cursor::new(b"abc");
+    let other = Cursor::new(b"xyz");
+    c.adopt(&other);
+}
+
+#[test]
+fn parse_ipv4_octets() {
+    let mut c = Cursor::new(b"192.168.0.1:80");
+    assert_eq!(c.parse_ipv4(), Some([192, 168, 0, 1]));
+    assert_eq!(c.peek(), Some(b':'));
+
+    for bad in [
+        &b"256.0.0.1"[..],
+        b"1.2.3",
+        b"1.2.3.",
+        b"1.2.3.1000",
+        b"1..2.3",
+    ] {
+        let mut c = Cursor::new(bad);
+        assert_eq!(c.parse_ipv4(), None, "{:?}", std::str::from_utf8(bad));
+        assert_eq!(c.pos(), 0);
+    }
+}
+
+#[test]
+fn take_ranges_multiple_classes() {
+    let input = b"abc123XYZ";
+    let mut c = Cursor::new(input);
+    let span = c.take_ranges(&[(b'a', b'z'), (b'0', b'9')]).unwrap();
+    assert_eq!(&input[span], b"abc123");
+    assert_no_span(c.take_ranges(&[(b'a', b'z'), (b'0', b'9')]));
+    assert_eq!(c.peek(), Some(b'X'));
+}
+
+#[test]
+fn hexdump_renders_bytes() {
+    let mut c = Cursor::new(b"Hello, world!\x00\x01\x02 more bytes here");
+    let dump = c.hexdump_span(0..20);
+    assert!(dump.starts_with("00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 00 01 02"));
+    assert!(dump.contains("|Hello, world!...|"));
+    assert!(dump.contains("00000010  20 6d 6f 72"));
+
+    c.advance(4);
+    let around = c.hexdump_around(2);
+    let mut lines = around.lines();
+    assert_eq!(
+        lines.next(),
+        Some("00000002  6c 6c 6f 2c 20                                    |llo, |")
+    );
+    assert_eq!(lines.next(), Some("                ^^")); // under `6f`
+}
+
+#[test]
+fn consume_str_validates_utf8() {
+    let mut c = Cursor::new("\x05héllo".as_bytes());
+    let len = c.next_byte().unwrap() as usize;
+    assert_eq!(c.consume_str(len + 1), Some("héllo"));
+    assert!(c.eof());
+
+    let mut bad = Cursor::new(b"ab\xFFcd");
+    assert_eq!(bad.consume_str(4), None);
+    assert_eq!(bad.pos(), 0);
+    assert_eq!(bad.consume_str(10), None); // not enough bytes
+    assert_eq!(bad.consume_str(2), Some("ab"));
+}
+
+#[test]
+fn to_owned_remaining_copies_tail() {
+    let mut c = Cursor::new(b"head:tail");
+    c.skip_until(b':');
+    c.next_byte();
+    let owned = c.to_owned_remaining();
+    assert_eq!(owned, b"tail");
+    assert_eq!(c.pos(), 5);
+}
+
+#[test]
+fn skip_unicode_space_handles_nbsp() {
+    let mut c = Cursor::new(" \u{A0}\u{3000}x".as_bytes());
+    assert_eq!(c.skip_unicode_space(), 1 + 2 + 3);
+    assert_eq!(c.peek(), Some(b'x'));
+    assert_eq!(c.skip_unicode_space(), 0);
+
+    let mut bad = Cursor::new(b" \xFF ");
+    assert_eq!(bad.skip_unicode_space(), 1);
+    assert_eq!(bad.peek(), Some(0xFF));
+}
+
+#[test]
+fn skip_lines_jumps_ahead() {
+    let input = b"one\ntwo\nthree\nfour\nfive";
+    let mut c = Cursor::new(input);
+    assert_eq!(c.skip_lines(2), 2);
+    assert_eq!(c.pos(), 8);
+    assert!(c.starts_with(b"three"));
+
+    assert_eq!(c.skip_lines(10), 2); // only two terminators left
+    assert!(c.eof());
+}
+
+#[test]
+fn match_bytes_prefix_reports_partial_match() {
+    let mut c = Cursor::new(b"foobaz");
+    assert_eq!(c.match_bytes_prefix(b"foobar"), Err(5));
+    assert_eq!(c.pos(), 0);
+    assert_eq!(c.match_bytes_prefix(b"foo"), Ok(0..3));
+
+    let mut short = Cursor::new(b"foo");
+    assert_eq!(short.match_bytes_prefix(b"foobar"), Err(3));
+}
+
+#[test]
+fn at_word_start_checks_previous_byte() {
+    let mut c = Cursor::new(b"xif if");
+    assert!(c.at_word_start());
+    c.advance(1);
+    assert!(!c.at_word_start()); // mid-identifier
+    c.advance(3);
+    assert!(c.at_word_start()); // after a space
+}
+
+#[test]
+fn skip_json_value_balances_brackets() {
+    let input = br#"{"a": [1, {"b": null}], "c": "x"} rest"#;
+    let mut c = Cursor::new(input);
+    let span = c.skip_json_value().unwrap();
+    assert_eq!(span, 0..input.len() - 5);
+    assert_eq!(c.as_slice(), b" rest");
+
+    let input = br#""brackets ] } and \" quote" tail"#;
+    let mut s = Cursor::new(input);
+    let span = s.skip_json_value().unwrap();
+    assert_eq!(&input[span], br#""brackets ] } and \" quote""#);
+
+    let mut num = Cursor::new(b"-12.5e3,");
+    assert_eq!(num.skip_json_value(), Some(0..7));
+    let mut num = Cursor::new(b"0]");
+    assert_eq!(num.skip_json_value(), Some(0..1));
+    let mut lit = Cursor::new(b"true,");
+    assert_eq!(lit.skip_json_value(), Some(0..4));
+
+    for bad in [
+        &b"-"[..],
+        b"--",
+        b"1-2e",
+        b"01",
+        b"1.",
+        b"1e+",
+        b"-.5",
+        b"truex",
+        b"nullnull",
+        b"false1",
+    ] {
+        let mut c = Cursor::new(bad);
+        assert_no_span(c.skip_json_value());
+        assert_eq!(c.pos(), 0);
+    }
+
+    for bad in [&br#"{"a": [1}"#[..], br#"["unterminated"#, b"nope"] {
+        let mut c = Cursor::new(bad);
+        assert_no_span(c.skip_json_value());
+        assert_eq!(c.pos(), 0);
+    }
+}
+
+#[test]
+fn skip_while_pair_stops_before_digraph() {
+    let mut c = Cursor::new(b"a * b */ rest");
+    let n = c.skip_while_pair(|b, next| !(b == b'*' && next == Some(b'/')));
+    assert_eq!(n, 6);
+    assert!(c.starts_with(b"*/"));
+}
+
+#[test]
+fn progress_fraction() {
+    assert_eq!(Cursor::new(b"").progress(), 0.0);
+
+    let mut c = Cursor::new(b"abcd");
+    assert_eq!(c.progress(), 0.0);
+    c.advance(2);
+    assert_eq!(c.progress(), 0.5);
+    c.advance(2);
+    assert_eq!(c.progress(), 1.0);
+}
+
+#[test]
+fn read_length_prefixed_frames() {
+    let mut c = Cursor::new(b"\x00\x03abc\x05\x00hi");
+    assert_eq!(c.read_length_prefixed(2, true), Some(&b"abc"[..]));
+    assert_eq!(c.pos(), 5);
+
+    // Little-endian length 5 but only 2 payload bytes remain.
+    assert_eq!(c.read_length_prefixed(2, false), None);
+    assert_eq!(c.pos(), 5);
+}
+
+#[test]
+fn rebind_reports_clamping() {
+    let mut c = Cursor::new(b"first chunk");
+    c.advance(6);
+    assert!(!c.rebind(b"first chunk, extended"));
+    assert_eq!(c.pos(), 6);
+
+    assert!(c.rebind(b"firs"));
+    assert_eq!(c.pos(), 4);
+    assert!(c.eof());
+}
+
+#[test]
+fn expect_bytes_not_followed_by_operator() {
+    use cursor_core::ByteSet;
+
+    let eq = ByteSet::from_bytes(b"=");
+    let mut c = Cursor::new(b"== x");
+    assert_no_span(c.expect_bytes_not_followed_by(b"=", &eq));
+    assert_eq!(c.pos(), 0);
+    assert_eq!(c.expect_bytes_not_followed_by(b"==", &eq), Some(0..2));
+
+    let mut end = Cursor::new(b"=");
+    assert_eq!(end.expect_bytes_not_followed_by(b"=", &eq), Some(0..1));
+}
+
+#[test]
+fn take_while_map_collects_digit_values() {
+    let mut c = Cursor::new(b"123x");
+    let digits = c.take_while_map(|b| (b as char).to_digit(10));
+    assert_eq!(digits, [1, 2, 3]);
+    assert_eq!(c.peek(), Some(b'x'));
+    assert!(c.take_while_map(|b| (b as char).to_digit(10)).is_empty());
+}
+
+#[test]
+fn take_crlf_line_is_strict() {
+    let input = b"HTTP/1.1 200 OK\r\nHost: x\nbody";
+    let mut c = Cursor::new(input);
+    let line = c.take_crlf_line().unwrap();
+    assert_eq!(&input[line], b"HTTP/1.1 200 OK");
+
+    let at = c.pos();
+    assert_no_span(c.take_crlf_line()); // bare LF
+    assert_eq!(c.pos(), at);
+
+    let mut eof = Cursor::new(b"no terminator\r");
+    assert_no_span(eof.take_crlf_line());
+}
+
+#[test]
+fn read_trait_copies_and_advances() {
+    use std::io::Read;
+
+    let mut c = Cursor::new(b"MAGIC payload");
+    assert!(c.expect_bytes(b"MAGIC ").is_some());
+
+    let mut out = [0u8; 4];
+    assert_eq!(c.read(&mut out).unwrap(), 4);
+    assert_eq!(&out, b"payl");
+    assert_eq!(c.pos(), 10);
+
+    let mut rest = Vec::new();
+    c.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"oad");
+    assert_eq!(c.read(&mut out).unwrap(), 0);
+}
+
+#[test]
+fn take_through_any_reports_delimiter() {
+    let input = b"a;b,c";
+    let mut c = Cursor::new(input);
+    assert_eq!(c.take_through_any(b";,"), (0..1, Some(b';')));
+    assert_eq!(c.take_through_any(b";,"), (2..3, Some(b',')));
+    assert_eq!(c.take_through_any(b";,"), (4..5, None));
+    assert!(c.eof());
+}
+
+#[test]
+fn check_magic_outcomes() {
+    use cursor_core::MagicResult;
+
+    let mut c = Cursor::new(b"%PDF-1.7");
+    assert_eq!(c.check_magic(b"%PDF-"), MagicResult::Match);
+    assert_eq!(c.pos(), 5);
+
+    let mut wrong = Cursor::new(b"%PNG");
+    assert_eq!(
+        wrong.check_magic(b"%PDF-"),
+        MagicResult::Mismatch {
+            at: 2,
+            expected: b'D',
+            found: b'N'
+        }
+    );
+    assert_eq!(wrong.pos(), 0);
+
+    let mut short = Cursor::new(b"%PD");
+    assert_eq!(
+        short.check_magic(b"%PDF-"),
+        MagicResult::Truncated { matched: 3 }
+    );
+    assert_eq!(short.pos(), 0);
+}
+
+#[test]
+fn rolling_boundary_is_deterministic() {
+    const WINDOW: usize = 4;
+    const MASK: u64 = 0x0F;
+
+    fn window_hash(w: &[u8]) -> u64 {
+        w.iter().fold(0u64, |h, &b| {
+            h.wrapping_mul(Cursor::ROLLING_HASH_BASE)
+                .wrapping_add(b as u64)
+        })
+    }
+
+    let input: Vec<u8> = (0..4096u32)
+        .map(|k| (k.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect();
+    let expected_end = (WINDOW..=input.len())
+        .find(|&end| window_hash(&input[end - WINDOW..end]) & MASK == 0)
+        .expect("boundary in test input");
+
+    let mut c = Cursor::new(&input);
+    let chunk = c.advance_until_rolling_boundary(WINDOW, MASK);
+    assert_eq!(chunk, 0..expected_end);
+
+    // Chunking is reproducible on a fresh cursor.
+    let mut again = Cursor::new(&input);
+    assert_eq!(again.advance_until_rolling_boundary(WINDOW, MASK), chunk);
+
+    // A window longer than the input never completes, so the scan runs to EOF.
+    let mut all = Cursor::new(b"abc");
+    assert_eq!(all.advance_until_rolling_boundary(8, u64::MAX), 0..3);
+}
+
+#[test]
+fn take_ident_str_returns_text() {
+    let mut c = Cursor::new(b"foo_bar123 = 1");
+    assert_eq!(c.take_ident_str(), Some("foo_bar123"));
+    assert_eq!(c.take_ident_str(), None);
+    assert_eq!(c.peek(), Some(b' '));
+}
+
+#[test]
+fn peek_token_class_reports_tag_and_len() {
+    use cursor_core::ByteSet;
+
+    const DIGIT: u32 = 1;
+    const IDENT: u32 = 2;
+    let digits = ByteSet::from_range(b'0', b'9');
+    let ident = ByteSet::from_range(b'a', b'z').union(digits).with(b'_');
+    let classes = [(&digits, DIGIT), (&ident, IDENT)];
+
+    let mut c = Cursor::new(b"123 abc_9");
+    assert_eq!(c.peek_token_class(&classes), Some((DIGIT, 3)));
+    assert_eq!(c.pos(), 0);
+    c.advance(4);
+    assert_eq!(c.peek_token_class(&classes), Some((IDENT, 5)));
+    c.advance(5);
+    assert_eq!(c.peek_token_class(&classes), None);
+}
+
+#[test]
+fn advance_all_or_nothing_vs_saturating() {
+    let mut c = Cursor::new(b"abc");
+    assert_no_span(c.advance(4));
+    assert_eq!(c.pos(), 0);
+    assert_eq!(assert_span_len(c.advance(2), 2), 0..2);
+    assert_no_span(c.advance(2));
+    assert_eq!(c.pos(), 2);
+
+    let mut s = Cursor::new(b"abc");
+    assert_eq!(s.advance_saturating(2), 2);
+    assert_eq!(s.advance_saturating(10), 1);
+    assert!(s.eof());
+    assert_eq!(s.advance_saturating(1), 0);
+}
+
+#[test]
+fn take_while_char_counting_multibyte() {
+    let input = "héllo wörld".as_bytes();
+    let mut c = Cursor::new(input);
+    let (span, chars) = c.take_while_char_counting(char::is_alphabetic);
+    assert_eq!(span, 0..6);
+    assert_eq!(chars, 5);
+    assert_eq!(c.peek(), Some(b' '));
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn trace_records_mark_and_reset() {
+    use cursor_core::trace::{CAPACITY, TraceEvent};
+
+    let mut c = Cursor::new(b"abcdef");
+    let m = c.mark();
+    c.advance(3);
+    c.reset(m);
+    assert_eq!(
+        c.trace_events(),
+        [
+            TraceEvent::Mark { pos: 0 },
+            TraceEvent::Reset { from: 3, to: 0 },
+        ]
+    );
+
+    // Only the most recent events are kept.
+    for _ in 0..CAPACITY + 5 {
+        c.mark();
+    }
+    let events = c.trace_events();
+    assert_eq!(events.len(), CAPACITY);
+    assert!(events.iter().all(|e| *e == TraceEvent::Mark { pos: 0 }));
+}
+
+#[test]
+fn peek_tail_reads_trailer() {
+    let mut c = Cursor::new(b"\x01body\xDE\xAD\xBE\xEF");
+    c.next_byte();
+    assert_eq!(c.peek_tail(4), Some(&b"\xDE\xAD\xBE\xEF"[..]));
+    assert_eq!(c.pos(), 1);
+    assert_eq!(c.peek_tail(8), Some(c.as_slice()));
+    assert_eq!(c.peek_tail(9), None);
+}
+
+#[test]
+fn expect_single_space_strictness() {
+    let mut c = Cursor::new(b"GET / HTTP/1.1");
+    c.take_ident_ascii();
+    assert!(c.expect_single_space(true));
+    assert_eq!(c.peek(), Some(b'/'));
+
+    let mut double = Cursor::new(b"GET  /");
+    double.take_ident_ascii();
+    assert!(!double.expect_single_space(true));
+    assert_eq!(double.pos(), 3);
+    assert!(double.expect_single_space(false)); // lenient mode takes one space
+    assert_eq!(double.pos(), 4);
+}
+
+#[test]
+fn skip_to_next_line_starting_with_resyncs() {
+    let input = b"[1] start\n  detail\n  more\n[2] next\nplain";
+    let mut c = Cursor::new(input);
+    assert!(c.skip_to_next_line_starting_with(b"["));
+    assert!(c.starts_with(b"[2]"));
+    assert_eq!(c.pos(), 26);
+
+    assert!(!c.skip_to_next_line_starting_with(b"["));
+    assert!(c.eof());
+}
+
+#[test]
+fn count_occurrences_non_overlapping() {
+    let c = Cursor::new(b"ababab");
+    assert_eq!(c.count_occurrences(b"ab"), 3);
+    assert_eq!(c.count_occurrences(b"aba"), 1); // non-overlapping
+    assert_eq!(c.count_occurrences(b"x"), 0);
+    assert_eq!(c.count_occurrences(b""), 0);
+    assert_eq!(c.pos(), 0);
+}
+
+#[test]
+fn unchecked_str_over_scanned_ascii() {
+    let mut c = Cursor::new(b"ident rest");
+    let span = c.take_ident_ascii().unwrap();
+    // SAFETY: `take_ident_ascii` only accepts ASCII bytes.
+    assert_eq!(unsafe { c.str_of_unchecked(span) }, "ident");
+    // SAFETY: the whole input is an ASCII literal.
+    assert_eq!(unsafe { c.remaining_str_unchecked() }, " rest");
+}
+
+#[test]
+fn remaining_guards() {
+    let mut c = Cursor::new(b"\x00\x00\x00\x2A\x01");
+    assert!(c.has_remaining(4));
+    c.advance(1);
+    assert!(c.has_remaining(4)); // exactly 4 left
+    assert!(!c.has_remaining(5));
+    assert_eq!(c.require_remaining(4), Some(()));
+    assert_eq!(c.require_remaining(5), None);
+}
+
+#[test]
+fn split_cursor_complementary_halves() {
+    let mut c = Cursor::new(b"HDR:body");
+    c.skip_until(b':');
+    c.next_byte();
+    let (head, tail) = c.split_cursor();
+    assert_eq!(head.as_slice(), b"HDR:");
+    assert_eq!(tail.as_slice(), b"body");
+    assert_eq!((head.pos(), tail.pos()), (0, 0));
+    assert_eq!(head.len() + tail.len(), c.len());
+}
+
+#[test]
+fn consume_while_prefix_counts_segments() {
+    let mut c = Cursor::new(b"../../../x");
+    assert_eq!(c.consume_while_prefix(b"../"), 3);
+    assert_eq!(c.as_slice(), b"x");
+    assert_eq!(c.consume_while_prefix(b"../"), 0);
+    assert_eq!(c.consume_while_prefix(b""), 0);
+}
+
+#[test]
+fn current_class_table_lookup() {
+    let mut table = [0u8; 256];
+    for b in b'0'..=b'9' {
+        table[b as usize] = 1;
+    }
+    for b in (b'a'..=b'z').chain(b'A'..=b'Z') {
+        table[b as usize] = 2;
+    }
+
+    let mut c = Cursor::new(b"7q-");
+    assert_eq!(c.current_class(&table), Some(1));
+    c.next_byte();
+    assert_eq!(c.current_class(&table), Some(2));
+    c.next_byte();
+    assert_eq!(c.current_class(&table), Some(0));
+    c.next_byte();
+    assert_eq!(c.current_class(&table), None);
+}
+
+#[test]
+fn finish_returns_unparsed_tail() {
+    let mut c = Cursor::new(b"v1;trailing data");
+    assert!(c.expect_bytes(b"v1;").is_some());
+    assert_eq!(c.finish(), b"trailing data");
+}
+
+#[test]
+fn parse_f64_in_range_bounds() {
+    let mut c = Cursor::new(b"0.75,1.5e0,-2,abc");
+    assert_eq!(c.parse_f64_in_range(0.0, 1.0), Some(0.75));
+    assert!(c.expect_byte(b',').is_some());
+
+    let at = c.pos();
+    assert_eq!(c.parse_f64_in_range(0.0, 1.0), None); // 1.5 is out of range
+    assert_eq!(c.pos(), at);
+    assert_eq!(c.parse_f64_in_range(0.0, 2.0), Some(1.5));
+    c.next_byte();
+    assert_eq!(c.parse_f64_in_range(-5.0, 5.0), Some(-2.0));
+    c.next_byte();
+
+    let at = c.pos();
+    assert_eq!(c.parse_f64_in_range(f64::MIN, f64::MAX), None); // malformed
+    assert_eq!(c.pos(), at);
+
+    let mut nan_bounds = Cursor::new(b"1.0");
+    assert_eq!(nan_bounds.parse_f64_in_range(f64::NAN, f64::NAN), None);
+    assert_eq!(nan_bounds.pos(), 0);
+}
+
+#[test]
+fn for_each_while_streams_bytes() {
+    let mut c = Cursor::new(b"4096 bytes");
+    let mut digits = Vec::new();
+    let n = c.for_each_while(|b| b.is_ascii_digit(), |b| digits.push(b));
+    assert_eq!(n, 4);
+    assert_eq!(digits, b"4096");
+    assert_eq!(c.peek(), Some(b' '));
+}
+
+#[test]
+fn peek_slice_semantics() {
+    let mut c = Cursor::new(b"abcdef");
+
+    // Start/mid-buffer: exact vs. up-to.
+    assert_eq!(c.peek_slice(3), Some(&b"abc"[..]));
+    assert_eq!(c.peek_slice_upto(3), b"abc");
+    c.advance(4);
+    assert_eq!(c.peek_slice(3), None);
+    assert_eq!(c.peek_slice_upto(3), b"ef");
+    assert_eq!(c.peek_slice(0), Some(&b""[..]));
+
+    // Pathological lengths never panic.
+    assert_eq!(c.peek_slice(usize::MAX), None);
+    assert_eq!(c.peek_slice_upto(usize::MAX), b"ef");
+    assert_eq!(c.peek_n(usize::MAX), None);
+
+    // At EOF.
+    c.advance(2);
+    assert_eq!(c.peek_slice(1), None);
+    assert_eq!(c.peek_slice(0), Some(&b""[..]));
+    assert_eq!(c.peek_slice_upto(4), b"");
+    assert_eq!(c.pos(), 6);
+}
+
+#[test]
+fn take_logical_line_folds_continuations() {
+    let mut c = Cursor::new(b"a\\\nb\nc\\\n\\\nd");
+    let span = c.take_logical_line().unwrap();
+    assert_eq!(&c.buffer()[span], b"a\\\nb");
+    assert_eq!(c.pos(), 5);
+
+    // Several continuations, last line unterminated.
+    let span = c.take_logical_line().unwrap();
+    assert_eq!(&c.buffer()[span], b"c\\\n\\\nd");
+    assert!(c.eof());
+    assert_eq!(c.take_logical_line(), None);
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn mark_named_records_label() {
+    use cursor_core::trace::TraceEvent;
+
+    let mut c = Cursor::new(b"key = value");
+    c.take_ident_ascii();
+    let m = c.mark_named("before_eq");
+    c.advance(3);
+    c.reset(m);
+    assert_eq!(
+        c.trace_events(),
+        [
+            TraceEvent::NamedMark {
+                pos: 3,
+                label: "before_eq"
+            },
+            TraceEvent::Reset { from: 6, to: 3 },
+        ]
+    );
+}
+
+#[test]
+fn chunks_leaves_partial_record() {
+    let mut c = Cursor::new(b"0123456789");
+    let records: Vec<&[u8]> = c.chunks(4).collect();
+    assert_eq!(records, [&b"0123"[..], &b"4567"[..]]);
+    assert_eq!(c.remaining(), 2);
+    assert_eq!(c.as_slice(), b"89");
+}
+
+#[test]
+fn parse_i64_bounds() {
+    let mut c = Cursor::new(b"-9223372036854775808");
+    assert_eq!(c.parse_i64(), Some(i64::MIN));
+    assert!(c.eof());
+
+    for input in [&b"-9223372036854775809"[..], b"9223372036854775808", b"-x"] {
+        let mut c = Cursor::new(input);
+        assert_eq!(c.parse_i64(), None);
+        assert_eq!(c.pos(), 0);
+    }
+
+    let mut c = Cursor::new(b"+5,");
+    assert_eq!(c.parse_i64(), Some(5));
+    assert_eq!(c.pos(), 2);
+}
+
+#[test]
+fn skip_to_first_of_finds_earliest_marker() {
+    let needles: [&[u8]; 2] = [b"-->", b"]]>"];
+    let mut c = Cursor::new(b"a -- b ]]> c --> d");
+    assert_eq!(c.skip_to_first_of(&needles), Some(1));
+    assert!(c.starts_with(b"]]>"));
+    c.advance(3);
+    assert_eq!(c.skip_to_first_of(&needles), Some(0));
+    assert!(c.starts_with(b"-->"));
+    c.advance(3);
+    assert_eq!(c.skip_to_first_of(&needles), None);
+    assert!(c.eof());
+
+    // An empty needle matches in place, but an earlier needle still wins.
+    let mut c = Cursor::new(b"xyz");
+    assert_eq!(c.skip_to_first_of(&[b"x", b""]), Some(0));
+    assert_eq!(c.skip_to_first_of(&[b"y", b""]), Some(1));
+    assert_eq!(c.pos(), 0);
+}
+
+#[test]
+fn scan_budget_caps_long_scans() {
+    let input = [b'a'; 100];
+    let mut c = Cursor::with_budget(&input, 40);
+    assert_eq!(Cursor::skip_while(&mut c, |b| b == b'a'), 0..40);
+    assert!(c.budget_exhausted());
+    assert_eq!(c.budget(), 0);
+    assert_eq!(Cursor::take_while(&mut c, |b| b == b'a'), None);
+    assert_eq!(c.skip_until(b'z'), 40..40);
+    assert_eq!(c.pos(), 40);
+
+    // A scan that ends on its own does not trip the budget.
+    let mut c = Cursor::with_budget(b"abc def", 5);
+    assert_eq!(Cursor::take_while(&mut c, |b| b != b' '), Some(0..3));
+    assert!(!c.budget_exhausted());
+    assert_eq!(c.budget(), 2);
+
+    // Forks share the remaining budget and hand it back on adopt.
+    let mut f = c.fork();
+    f.skip_byte(b' ');
+    f.skip_until(b'\n');
+    assert!(f.budget_exhausted());
+    c.adopt(&f);
+    assert!(c.budget_exhausted());
+    assert_eq!(c.pos(), 6);
+}
+
+#[test]
+fn scan_budget_stops_line_skipping() {
+    let mut c = Cursor::with_budget(b"aaaaaaaa\nb\nc\n", 3);
+    assert_eq!(c.skip_lines(2), 0);
+    assert!(c.budget_exhausted());
+    assert_eq!(c.pos(), 3);
+
+    let mut c = Cursor::with_budget(b"aaaaaaaa\nb\n[c]\n", 3);
+    assert!(!c.skip_to_next_line_starting_with(b"["));
+    assert!(c.budget_exhausted());
+    assert_eq!(c.pos(), 3);
+}
+
+#[test]
+fn scan_budget_fails_token_parsers() {
+    let mut c = Cursor::with_budget(b"abcdef;x", 3);
+    assert_eq!(c.take_through_any(b";"), (0..3, None));
+    assert_eq!(c.pos(), 3);
+
+    // Parsers roll back instead of returning a truncated token.
+    let mut c = Cursor::with_budget(b"12345", 2);
+    assert_eq!(c.parse_u64_ascii(), None);
+    assert_eq!(c.pos(), 0);
+    assert!(c.budget_exhausted());
+    let mut c = Cursor::with_budget(b"abcdef\n", 3);
+    assert_eq!(c.take_line_info(), None);
+    assert_eq!(c.pos(), 0);
+    let mut c = Cursor::with_budget(b"12345", 2);
+    assert_eq!(c.skip_json_value(), None);
+    assert_eq!(c.pos(), 0);
+
+    // Within budget they behave as usual, and the flag stays clear.
+    let mut c = Cursor::with_budget(b"12345", 8);
+    assert_eq!(c.parse_u64_ascii(), Some(12345));
+    assert!(!c.budget_exhausted());
+
+    // Independent scanners do not inherit the budget.
+    let c = Cursor::with_budget(b"12345", 2);
+    assert_eq!(c.scanner_at(0).budget(), usize::MAX);
+    assert_eq!(c.fork().budget(), 2);
+}
+
+#[test]
+fn scan_budget_allows_exact_fit() {
+    let mut c = Cursor::with_budget(b"abc def", 3);
+    assert_eq!(c.take_ident_ascii(), Some(0..3));
+    assert!(!c.budget_exhausted());
+    assert_eq!(c.budget(), 0);
+
+    let mut c = Cursor::with_budget(b"12 ", 2);
+    assert_eq!(c.parse_u64_ascii(), Some(12));
+    assert!(!c.budget_exhausted());
+
+    let mut c = Cursor::with_budget(b"abc\n", 3);
+    assert_eq!(c.skip_until(b'\n'), 0..3);
+    assert!(!c.budget_exhausted());
+}
+
+#[test]
+fn scan_budget_covers_predicate_scanners() {
+    use core::ops::ControlFlow;
+    use cursor_core::ByteSet;
+
+    let input = [b'a'; 1000];
+    let fresh = || Cursor::with_budget(&input, 10);
+    type Scan = fn(&mut Cursor) -> usize;
+    let checks: [(&str, Scan); 9] = [
+        ("skip_while_result", |c| {
+            c.skip_while_result(|_| ControlFlow::Continue(()))
+        }),
+        ("skip_while_pair", |c| c.skip_while_pair(|_, _| true)),
+        ("for_each_while", |c| c.for_each_while(|_| true, |_| {})),
+        ("take_while_map", |c| c.take_while_map(Some).len()),
+        ("take_while_char_counting", |c| {
+            c.take_while_char_counting(|_| true).1
+        }),
+        ("take_escaped_until", |c| {
+            c.take_escaped_until(b'\\', |_| false).0.len()
+        }),
+        ("take_arg", |c| c.take_arg(b',', b'(', b')').len()),
+        ("take_ident_starting_alpha", |c| {
+            c.take_ident_starting_alpha().map_or(0, |s| s.len())
+        }),
+        ("take_set_bounded", |c| {
+            let set = ByteSet::from_bytes(b"a");
+            c.take_set_bounded(&set, 1, 500).map_or(0, |s| s.len())
+        }),
+    ];
+    for (name, scan) in checks {
+        let mut c = fresh();
+        let n = scan(&mut c);
+        assert!(n <= 10 && c.pos() <= 10, "{name} scanned past the budget");
+        assert!(c.budget_exhausted(), "{name} did not report exhaustion");
+    }
+    let mut c = fresh();
+    assert_eq!(c.expect_run(b'a', 1, 500), None);
+    assert_eq!(c.pos(), 0);
+
+    // Hitting `max` is not exhaustion.
+    let mut c = fresh();
+    assert_eq!(c.expect_run(b'a', 1, 4), Some(0..4));
+    assert!(!c.budget_exhausted());
+
+    // JSON containers and strings are scanned under the budget too.
+    let json = br#"[1, "abc"] "#;
+    let mut c = Cursor::with_budget(json, 5);
+    assert_eq!(c.skip_json_value(), None);
+    assert!(c.budget_exhausted());
+    assert_eq!(c.pos(), 0);
+    let mut c = Cursor::with_budget(json, 10);
+    assert_eq!(c.skip_json_value(), Some(0..10));
+    assert!(!c.budget_exhausted());
+}
+
+#[test]
+fn consume_dedent_detects_smaller_indent() {
+    let mut c = Cursor::new(b"  x");
+    assert_eq!(c.consume_dedent(4, 4), Some(2));
+    assert_eq!(c.peek(), Some(b'x'));
+
+    // Same or deeper indentation is not a dedent.
+    let mut c = Cursor::new(b"    x");
+    assert_eq!(c.consume_dedent(4, 4), None);
+    assert_eq!(c.pos(), 0);
+    let mut c = Cursor::new(b"\tx");
+    assert_eq!(c.consume_dedent(4, 4), None);
+    assert_eq!(c.consume_dedent(8, 4), Some(4));
+
+    // Blank lines don't close blocks, and only line starts are measured.
+    let mut c = Cursor::new(b"\n    x");
+    assert_eq!(c.consume_dedent(4, 4), None);
+    c.advance(1);
+    assert_eq!(c.consume_dedent(8, 4), Some(4));
+    let mut c = Cursor::new(b"  \r\nx  y");
+    assert_eq!(c.consume_dedent(4, 4), None);
+    c.advance(5);
+    assert_eq!(c.consume_dedent(4, 4), None);
+    assert_eq!(c.pos(), 5);
+}
+
+#[test]
+fn expect_bytes_masked_mixes_case_rules() {
+    // Method name is case-insensitive, the `/` path and version are not.
+    let pat = b"get /HTTP";
+    let mask = [true, true, true, false, false, false, false, false, false];
+    let mut c = Cursor::new(b"GeT /HTTP/1.1");
+    assert_eq!(c.expect_bytes_masked(pat, &mask), Some(0..9));
+
+    let mut c = Cursor::new(b"GET /http/1.1");
+    assert_eq!(c.expect_bytes_masked(pat, &mask), None);
+    assert_eq!(c.pos(), 0);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "ci_mask length")]
+fn expect_bytes_masked_rejects_short_mask() {
+    Cursor::new(b"abc").expect_bytes_masked(b"abc", &[true]);
+}
+
+#[cfg(not(debug_assertions))]
+#[test]
+fn expect_bytes_masked_short_mask_matches_exactly() {
+    let mut c = Cursor::new(b"aXY");
+    assert_eq!(c.expect_bytes_masked(b"abc", &[true]), None);
+    assert_eq!(c.pos(), 0);
+    let mut c = Cursor::new(b"Abc");
+    assert_eq!(c.expect_bytes_masked(b"abc", &[true]), Some(0..3));
+}
+
+#[test]
+fn peek_token_str_previews_without_advancing() {
+    use cursor_core::ByteSet;
+
+    let ident = ByteSet::from_range(b'a', b'z').with(b'_');
+    let mut c = Cursor::new(b"  user_name = 1");
+    c.take_space();
+    assert_eq!(c.peek_token_str(&ident), Some("user_name"));
+    assert_eq!(c.pos(), 2);
+    c.advance(9);
+    assert_eq!(c.peek_token_str(&ident), None);
+}
+
+#[test]
+fn advance_by_reports_shortfall() {
+    let mut c = Cursor::new(b"abcde");
+    assert_eq!(c.advance_by(3), Ok(()));
+    assert_eq!(c.advance_by(2), Ok(()));
+    assert!(c.eof());
+    assert_eq!(c.advance_by(0), Ok(()));
+
+    let mut c = Cursor::new(b"abcde");
+    c.advance(3);
+    assert_eq!(c.advance_by(5), Err(3));
+    assert_eq!(c.pos(), 5);
+}
+
+#[test]
+fn decode_with_table_maps_escapes() {
+    let mut table = [None; 256];
+    table[b'n' as usize] = Some(b'\n');
+    table[b't' as usize] = Some(b'\t');
+    table[b'"' as usize] = Some(b'"');
+
+    let mut c = Cursor::new(br#"a\tb\nc\"\q" rest"#);
+    let mut out = Vec::new();
+    assert!(c.decode_with_table(b'"', b'\\', &table, &mut out));
+    assert_eq!(out, b"a\tb\nc\"\\q");
+    assert_eq!(c.peek(), Some(b'"'));
+
+    let mut c = Cursor::new(b"open\\");
+    out.clear();
+    assert!(!c.decode_with_table(b'"', b'\\', &table, &mut out));
+    assert_eq!(out, b"open\\");
+    assert!(c.eof());
+}
+
+#[test]
+fn parse_u64_ascii_values_and_overflow() {
+    let mut c = Cursor::new(b"18446744073709551615,");
+    assert_eq!(c.parse_u64_ascii(), Some(u64::MAX));
+    assert_eq!(c.peek(), Some(b','));
+
+    let mut c = Cursor::new(b"18446744073709551616");
+    assert_eq!(c.parse_u64_ascii(), None);
+    assert_eq!(c.pos(), 0);
+    assert_eq!(Cursor::new(b"x1").parse_u64_ascii(), None);
+}
+
+#[test]
+fn is_token_boundary_checks_arbitrary_positions() {
+    let c = Cursor::new(b"foo bar");
+    assert!(c.is_token_boundary(0));
+    assert!(c.is_token_boundary(3));
+    assert!(c.is_token_boundary(4));
+    assert!(!c.is_token_boundary(2));
+    assert!(c.is_token_boundary(7));
+    assert!(!c.is_token_boundary(8));
+    assert_eq!(c.pos(), 0);
+}
+
+#[test]
+fn parse_i64_ascii_signs() {
+    let mut c = Cursor::new(b"-42 +7");
+    assert_eq!(c.parse_i64_ascii(), Some(-42));
+    c.take_space();
+    assert_eq!(c.parse_i64_ascii(), Some(7));
+
+    let mut c = Cursor::new(b"9223372036854775808");
+    assert_eq!(c.parse_i64_ascii(), None);
+    assert_eq!(c.pos(), 0);
+
+    let mut c = Cursor::new(b"- ");
+    assert_eq!(c.parse_i64_ascii(), None);
+    assert_eq!(c.peek(), Some(b'-'));
+}
+
+#[test]
+fn take_escaped_until_stops_on_control_byte() {
+    let mut c = Cursor::new(b"ab\\\tc\td");
+    let (span, found) = c.take_escaped_until(b'\\', |b| b.is_ascii_control());
+    assert!(found);
+    assert_eq!(&c.buffer()[span], b"ab\\\tc");
+    assert_eq!(c.peek(), Some(b'\t'));
+
+    let mut c = Cursor::new(b"xy\\");
+    let (span, found) = c.take_escaped_until(b'\\', |b| b.is_ascii_control());
+    assert!(!found);
+    assert_eq!(span, 0..3);
+    assert!(c.eof());
+}
+
+#[test]
+fn cursor_is_shareable_across_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Cursor<'static>>();
+
+    let parent = Cursor::new(b"key = value");
+    std::thread::scope(|s| {
+        let value = s.spawn(|| {
+            let mut sub = parent.scanner_at(6);
+            sub.take_ident_str()
+        });
+        assert_eq!(value.join().unwrap(), Some("value"));
+    });
+}